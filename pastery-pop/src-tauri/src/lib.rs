@@ -11,29 +11,162 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 
 const SETTINGS_FILE: &str = "pastery-pop.json";
+const THUMBNAIL_SIZE: u32 = 128;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct ClipboardItem {
+    id: u64,
     date: String,
-    sequence: i32,
     content: String,
     memo: Option<String>,
+    #[serde(default = "default_kind")]
+    kind: String,
+    #[serde(default)]
+    html: Option<String>,
+    #[serde(default)]
+    files: Option<Vec<FileEntry>>,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    sensitive: bool,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct FileEntry {
+    path: String,
+    exists: bool,
+}
+
+fn default_kind() -> String {
+    "text".to_string()
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct MemoItem {
-    sequence: i32,
+    id: u64,
+    memo: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    folder: Option<String>, // "work/sql"처럼 /로 구분한 경로, 없으면 최상위
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+// 메모 폴더 (GET /memo/folders), count는 하위 폴더의 메모까지 포함
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct MemoFolder {
+    path: String,
+    count: usize,
+}
+
+// 메모의 이전 버전 (GET /memo/{id}/revisions)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct MemoRevision {
+    revision: u64,
     memo: String,
+    date: String, // 그 내용으로 저장되었던 날짜
 }
 
 // 프론트엔드에서 사용할 통합 아이템 구조체
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct DisplayItem {
-    id: i32,        // sequence를 id로 사용
+    id: u64,        // 클립보드 항목과 메모가 함께 쓰는 전역 id
     date: Option<String>,
-    sequence: i32,
     content: String,
     memo: Option<String>,
+    title: Option<String>,       // 메모 제목
+    folder: Option<String>,      // 메모가 들어 있는 폴더
+    description: Option<String>, // 메모 설명
+    kind: String,                // "text" | "image" | "files"
+    image_url: Option<String>,   // 이미지 항목의 썸네일 URL
+    rich: bool,                  // HTML 서식이 함께 저장된 항목
+    files: Option<Vec<FileEntry>>,
+    pinned: bool,                // 고정된 클립보드 항목 (목록 맨 위, 정리 대상 제외)
+    sensitive: bool,             // 비밀 정보가 감지된 항목 (보기 전까지 내용을 가림)
+    tags: Vec<String>,
+    highlights: Vec<[usize; 2]>, // 검색 결과에서 일치한 구간 (content의 문자 위치)
+}
+
+impl DisplayItem {
+    fn from_clipboard(item: ClipboardItem, server_url: &str) -> Self {
+        DisplayItem {
+            id: item.id,
+            image_url: (item.kind == "image").then(|| format!(
+                "{}/clipboard/{}/image?max_size={}",
+                server_url, item.id, THUMBNAIL_SIZE
+            )),
+            date: Some(item.date),
+            content: item.content,
+            memo: item.memo,
+            title: None,
+            folder: None,
+            description: None,
+            kind: item.kind,
+            rich: item.html.is_some(),
+            files: item.files,
+            pinned: item.pinned,
+            sensitive: item.sensitive,
+            tags: item.tags,
+            highlights: Vec::new(),
+        }
+    }
+
+    fn from_memo(memo: MemoItem) -> Self {
+        DisplayItem {
+            id: memo.id,
+            date: None,
+            content: memo.memo.clone(),
+            memo: Some(memo.memo),
+            title: memo.title,
+            folder: memo.folder,
+            description: memo.description,
+            kind: default_kind(),
+            image_url: None,
+            rich: false,
+            files: None,
+            pinned: false,
+            sensitive: false,
+            tags: memo.tags,
+            highlights: Vec::new(),
+        }
+    }
+}
+
+// 휴지통의 클립보드 항목이나 메모 (GET /trash)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct TrashItem {
+    id: u64,
+    item_type: String,    // "clipboard" | "memo"
+    kind: Option<String>, // 클립보드 항목의 종류
+    date: String,         // 삭제한 날짜
+    content: String,
+    #[serde(default)]
+    sensitive: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SearchHit<T> {
+    item: T,
+    score: f64,
+    highlights: Vec<[usize; 2]>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct SearchResults<C, M> {
+    clipboard: Vec<C>,
+    memos: Vec<M>,
+}
+
+// GET /clipboard 응답의 한 페이지 (next_cursor로 이전 항목을 이어서 조회)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ClipboardPage<T> {
+    items: Vec<T>,
+    next_cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -83,27 +216,31 @@ fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+// sort: "created"(기본), "updated" 또는 "manual"(reorder_user_memos로 정한 순서)
+// folder: 그 폴더에 바로 들어 있는 메모만 (빈 문자열이면 최상위, 없으면 전체)
 #[tauri::command]
-async fn get_user_memos(count: Option<u32>) -> Result<Vec<DisplayItem>, String> {
+async fn get_user_memos(count: Option<u32>, sort: Option<String>, folder: Option<String>) -> Result<Vec<DisplayItem>, String> {
     let settings = load_settings();
     let count = count.unwrap_or(settings.max_items_display);
-    let url = format!("{}/memo?count={}", settings.server_url, count);
+    let url = format!("{}/memo", settings.server_url);
+
+    let mut params = vec![("count", count.to_string())];
+    if let Some(sort) = sort {
+        params.push(("sort", sort));
+    }
+    if let Some(folder) = folder {
+        params.push(("folder", folder));
+    }
     
     let client = reqwest::Client::new();
-    match client.get(&url).send().await {
+    match client.get(&url).query(&params).send().await {
         Ok(response) => {
             match response.json::<ApiResponse<Vec<MemoItem>>>().await {
                 Ok(api_response) => {
                     if api_response.success {
                         let display_items = api_response.data.unwrap_or_default()
                             .into_iter()
-                            .map(|memo| DisplayItem {
-                                id: memo.sequence,
-                                date: None,
-                                sequence: memo.sequence,
-                                content: memo.memo.clone(),
-                                memo: Some(memo.memo),
-                            })
+                            .map(DisplayItem::from_memo)
                             .collect();
                         Ok(display_items)
                     } else {
@@ -118,118 +255,239 @@ async fn get_user_memos(count: Option<u32>) -> Result<Vec<DisplayItem>, String>
 }
 
 #[tauri::command]
-async fn add_user_memo(memo_content: String) -> Result<(), String> {
+async fn add_user_memo(
+    memo_content: String,
+    title: Option<String>,
+    description: Option<String>,
+    folder: Option<String>,
+) -> Result<(), String> {
     let settings = load_settings();
     let url = format!("{}/memo", settings.server_url);
     
     let memo_data = serde_json::json!({
-        "memo": memo_content
+        "memo": memo_content,
+        "title": title,
+        "description": description,
+        "folder": folder
     });
     
     let client = reqwest::Client::new();
-    match client.post(&url)
-        .header("Content-Type", "application/json")
-        .json(&memo_data)
-        .send().await {
+    match client.post(&url).json(&memo_data).send().await {
         Ok(response) => {
-            // 응답 상태 코드 확인
-            let status = response.status();
-            println!("Response status: {}", status);
-            
-            // 응답 텍스트를 먼저 읽어서 로깅
-            match response.text().await {
-                Ok(text) => {
-                    println!("Response text: {}", text);
-                    
-                    // JSON 파싱 시도
-                    match serde_json::from_str::<ApiResponse<serde_json::Value>>(&text) {
-                        Ok(api_response) => {
-                            if api_response.success {
-                                Ok(())
-                            } else {
-                                Err(api_response.message)
-                            }
-                        }
-                        Err(e) => Err(format!("Failed to parse JSON response '{}': {}", text, e)),
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
                     }
                 }
-                Err(e) => Err(format!("Failed to read response text: {}", e)),
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
             }
         }
         Err(e) => Err(format!("Failed to add user memo: {}", e)),
     }
 }
 
+// title, description, folder는 None이면 그대로 두고 빈 문자열이면 지움 (folder는 최상위로)
 #[tauri::command]
-async fn update_user_memo(memo_id: i32, memo_content: String) -> Result<(), String> {
+async fn update_user_memo(
+    memo_id: u64,
+    memo_content: String,
+    title: Option<String>,
+    description: Option<String>,
+    folder: Option<String>,
+) -> Result<(), String> {
     let settings = load_settings();
     let url = format!("{}/memo", settings.server_url);
     
     let memo_data = serde_json::json!({
-        "sequence": memo_id,
-        "memo": memo_content
+        "id": memo_id,
+        "memo": memo_content,
+        "title": title,
+        "description": description,
+        "folder": folder
     });
     
     let client = reqwest::Client::new();
-    println!("Sending update request with memo_data: {:?}", memo_data);
-    
-    match client.put(&url)
-        .header("Content-Type", "application/json")
-        .json(&memo_data)
-        .send().await {
+    match client.put(&url).json(&memo_data).send().await {
         Ok(response) => {
-            let status = response.status();
-            println!("Update memo response status: {}", status);
-            
-            match response.text().await {
-                Ok(text) => {
-                    println!("Update memo response text: {}", text);
-                    
-                    match serde_json::from_str::<ApiResponse<serde_json::Value>>(&text) {
-                        Ok(api_response) => {
-                            if api_response.success {
-                                Ok(())
-                            } else {
-                                Err(api_response.message)
-                            }
-                        }
-                        Err(e) => Err(format!("Failed to parse JSON response '{}': {}", text, e)),
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
                     }
                 }
-                Err(e) => Err(format!("Failed to read response text: {}", e)),
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
             }
         }
         Err(e) => Err(format!("Failed to update user memo: {}", e)),
     }
 }
 
+// 메모의 이전 버전 (최근 버전부터)
+#[tauri::command]
+async fn get_memo_revisions(memo_id: u64) -> Result<Vec<MemoRevision>, String> {
+    let settings = load_settings();
+    let url = format!("{}/memo/{}/revisions", settings.server_url, memo_id);
+
+    let client = reqwest::Client::new();
+    match client.get(&url).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<Vec<MemoRevision>>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(api_response.data.unwrap_or_default())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to fetch memo revisions: {}", e)),
+    }
+}
+
+// 메모를 이전 버전의 내용으로 되돌림
+#[tauri::command]
+async fn restore_memo_revision(memo_id: u64, revision: u64) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/memo/{}/revisions/{}/restore", settings.server_url, memo_id, revision);
+
+    let client = reqwest::Client::new();
+    match client.post(&url).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to restore memo revision: {}", e)),
+    }
+}
+
+// 메모 폴더 목록 (경로 순, 상위 폴더 포함)
 #[tauri::command]
-async fn delete_user_memo(memo_id: i32) -> Result<(), String> {
+async fn get_memo_folders() -> Result<Vec<MemoFolder>, String> {
+    let settings = load_settings();
+    let url = format!("{}/memo/folders", settings.server_url);
+
+    let client = reqwest::Client::new();
+    match client.get(&url).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<Vec<MemoFolder>>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(api_response.data.unwrap_or_default())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to fetch memo folders: {}", e)),
+    }
+}
+
+// 폴더 이름 변경/이동 (하위 폴더 포함), to가 빈 문자열이면 안의 메모를 최상위로
+#[tauri::command]
+async fn rename_memo_folder(from: String, to: String) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/memo/folders", settings.server_url);
+
+    let client = reqwest::Client::new();
+    match client.put(&url).json(&serde_json::json!({ "from": from, "to": to })).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to rename memo folder: {}", e)),
+    }
+}
+
+// 메모를 폴더로 옮김, folder가 없거나 빈 문자열이면 최상위
+#[tauri::command]
+async fn move_user_memo(memo_id: u64, folder: Option<String>) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/memo/{}/folder", settings.server_url, memo_id);
+
+    let client = reqwest::Client::new();
+    match client.put(&url).json(&serde_json::json!({ "folder": folder })).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to move user memo: {}", e)),
+    }
+}
+
+// 메모를 ids 순서대로 맨 위부터 배치 (나머지 메모는 그 뒤에 기존 순서대로)
+#[tauri::command]
+async fn reorder_user_memos(ids: Vec<u64>) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/memo/order", settings.server_url);
+
+    let client = reqwest::Client::new();
+    match client.put(&url).json(&serde_json::json!({ "ids": ids })).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to reorder user memos: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn delete_user_memo(memo_id: u64) -> Result<(), String> {
     let settings = load_settings();
     let url = format!("{}/memo/{}", settings.server_url, memo_id);
     
     let client = reqwest::Client::new();
     match client.delete(&url).send().await {
         Ok(response) => {
-            let status = response.status();
-            println!("Delete memo response status: {}", status);
-            
-            match response.text().await {
-                Ok(text) => {
-                    println!("Delete memo response text: {}", text);
-                    
-                    match serde_json::from_str::<ApiResponse<serde_json::Value>>(&text) {
-                        Ok(api_response) => {
-                            if api_response.success {
-                                Ok(())
-                            } else {
-                                Err(api_response.message)
-                            }
-                        }
-                        Err(e) => Err(format!("Failed to parse JSON response '{}': {}", text, e)),
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
                     }
                 }
-                Err(e) => Err(format!("Failed to read response text: {}", e)),
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
             }
         }
         Err(e) => Err(format!("Failed to delete user memo: {}", e)),
@@ -237,28 +495,29 @@ async fn delete_user_memo(memo_id: i32) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn get_clipboard_items(count: Option<u32>) -> Result<Vec<DisplayItem>, String> {
+async fn get_clipboard_items(count: Option<u32>, before: Option<String>) -> Result<ClipboardPage<DisplayItem>, String> {
     let settings = load_settings();
     let count = count.unwrap_or(settings.max_items_display);
-    let url = format!("{}/clipboard?count={}", settings.server_url, count);
+    // 고정된 항목은 첫 페이지 맨 위에 함께 옴
+    let mut url = format!("{}/clipboard?count={}&pinned=first", settings.server_url, count);
+    if let Some(before) = before {
+        url.push_str(&format!("&before={}", before));
+    }
     
     let client = reqwest::Client::new();
     match client.get(&url).send().await {
         Ok(response) => {
-            match response.json::<ApiResponse<Vec<ClipboardItem>>>().await {
+            match response.json::<ApiResponse<ClipboardPage<ClipboardItem>>>().await {
                 Ok(api_response) => {
                     if api_response.success {
-                        let display_items = api_response.data.unwrap_or_default()
+                        let Some(page) = api_response.data else {
+                            return Ok(ClipboardPage { items: Vec::new(), next_cursor: None });
+                        };
+                        let display_items = page.items
                             .into_iter()
-                            .map(|item| DisplayItem {
-                                id: item.sequence,
-                                date: Some(item.date),
-                                sequence: item.sequence,
-                                content: item.content,
-                                memo: item.memo,
-                            })
+                            .map(|item| DisplayItem::from_clipboard(item, &settings.server_url))
                             .collect();
-                        Ok(display_items)
+                        Ok(ClipboardPage { items: display_items, next_cursor: page.next_cursor })
                     } else {
                         Err(api_response.message)
                     }
@@ -270,6 +529,326 @@ async fn get_clipboard_items(count: Option<u32>) -> Result<Vec<DisplayItem>, Str
     }
 }
 
+#[tauri::command]
+async fn search_items(
+    query: String,
+    count: Option<u32>,
+    mode: Option<String>,
+) -> Result<SearchResults<DisplayItem, DisplayItem>, String> {
+    let settings = load_settings();
+    let count = count.unwrap_or(settings.max_items_display);
+    let url = format!("{}/search", settings.server_url);
+
+    // mode: "fuzzy" 또는 "regex", 그 외에는 일반 검색
+    let mut params = vec![("q", query), ("count", count.to_string())];
+    match mode.as_deref() {
+        Some("fuzzy") => params.push(("fuzzy", "true".to_string())),
+        Some("regex") => params.push(("regex", "true".to_string())),
+        _ => {}
+    }
+
+    let client = reqwest::Client::new();
+    match client.get(&url).query(&params).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<SearchResults<SearchHit<ClipboardItem>, SearchHit<MemoItem>>>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        let Some(results) = api_response.data else {
+                            return Ok(SearchResults { clipboard: Vec::new(), memos: Vec::new() });
+                        };
+                        let clipboard = results.clipboard
+                            .into_iter()
+                            .map(|hit| DisplayItem {
+                                highlights: hit.highlights,
+                                ..DisplayItem::from_clipboard(hit.item, &settings.server_url)
+                            })
+                            .collect();
+                        let memos = results.memos
+                            .into_iter()
+                            .map(|hit| DisplayItem {
+                                highlights: hit.highlights,
+                                ..DisplayItem::from_memo(hit.item)
+                            })
+                            .collect();
+                        Ok(SearchResults { clipboard, memos })
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to search items: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn activate_clipboard_item(id: u64, plain: Option<bool>) -> Result<(), String> {
+    let settings = load_settings();
+    let format = if plain.unwrap_or(false) { "plain" } else { "rich" };
+    let url = format!("{}/clipboard/{}/activate?format={}", settings.server_url, id, format);
+
+    let client = reqwest::Client::new();
+    match client.post(&url).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to activate clipboard item: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn pin_clipboard_item(id: u64) -> Result<(), String> {
+    set_clipboard_item_pinned(id, true).await
+}
+
+#[tauri::command]
+async fn unpin_clipboard_item(id: u64) -> Result<(), String> {
+    set_clipboard_item_pinned(id, false).await
+}
+
+async fn set_clipboard_item_pinned(id: u64, pinned: bool) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/clipboard/{}/pin", settings.server_url, id);
+
+    let client = reqwest::Client::new();
+    let request = if pinned { client.post(&url) } else { client.delete(&url) };
+    match request.send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to pin clipboard item: {}", e)),
+    }
+}
+
+// 클립보드 항목에 붙은 메모 추가/수정
+#[tauri::command]
+async fn set_clipboard_item_memo(id: u64, memo: String) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/clipboard/{}/memo", settings.server_url, id);
+
+    let client = reqwest::Client::new();
+    match client.put(&url).json(&serde_json::json!({ "memo": memo })).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to set clipboard item memo: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn delete_clipboard_item_memo(id: u64) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/clipboard/{}/memo", settings.server_url, id);
+
+    let client = reqwest::Client::new();
+    match client.delete(&url).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to delete clipboard item memo: {}", e)),
+    }
+}
+
+// 클립보드 항목 본문 수정 (텍스트 항목만)
+#[tauri::command]
+async fn update_clipboard_item(id: u64, content: String) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/clipboard/{}", settings.server_url, id);
+
+    let client = reqwest::Client::new();
+    match client.put(&url).json(&serde_json::json!({ "content": content })).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to update clipboard item: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn delete_clipboard_item(id: u64) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/clipboard/{}", settings.server_url, id);
+
+    let client = reqwest::Client::new();
+    match client.delete(&url).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to delete clipboard item: {}", e)),
+    }
+}
+
+// 복사 시각(ms)이 [since, until) 범위인 항목 삭제, 삭제한 개수 반환
+#[tauri::command]
+async fn delete_clipboard_range(
+    since: Option<i64>,
+    until: Option<i64>,
+    include_pinned: Option<bool>,
+) -> Result<u64, String> {
+    let settings = load_settings();
+    let url = format!("{}/clipboard", settings.server_url);
+
+    let mut params = vec![("include_pinned", include_pinned.unwrap_or(false).to_string())];
+    if let Some(since) = since {
+        params.push(("since", since.to_string()));
+    }
+    if let Some(until) = until {
+        params.push(("until", until.to_string()));
+    }
+    delete_items(reqwest::Client::new().delete(&url).query(&params)).await
+}
+
+// 클립보드 기록 전체 삭제 (메모는 유지), 삭제한 개수 반환
+#[tauri::command]
+async fn clear_clipboard_history(include_pinned: Option<bool>) -> Result<u64, String> {
+    let settings = load_settings();
+    let url = format!("{}/clipboard/all", settings.server_url);
+
+    let params = [("include_pinned", include_pinned.unwrap_or(false).to_string())];
+    delete_items(reqwest::Client::new().delete(&url).query(&params)).await
+}
+
+// 휴지통 목록 (최근에 삭제한 항목부터)
+#[tauri::command]
+async fn get_trash_items() -> Result<Vec<TrashItem>, String> {
+    let settings = load_settings();
+    let url = format!("{}/trash", settings.server_url);
+
+    let client = reqwest::Client::new();
+    match client.get(&url).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<Vec<TrashItem>>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(api_response.data.unwrap_or_default())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to get trash items: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn restore_trash_item(id: u64) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/trash/{}/restore", settings.server_url, id);
+
+    let client = reqwest::Client::new();
+    match client.post(&url).send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(())
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to restore trash item: {}", e)),
+    }
+}
+
+// 휴지통의 항목을 완전히 삭제
+#[tauri::command]
+async fn delete_trash_item(id: u64) -> Result<(), String> {
+    let settings = load_settings();
+    let url = format!("{}/trash/{}", settings.server_url, id);
+
+    delete_items(reqwest::Client::new().delete(&url)).await.map(|_| ())
+}
+
+// 휴지통 비우기, 삭제한 개수 반환
+#[tauri::command]
+async fn empty_trash() -> Result<u64, String> {
+    let settings = load_settings();
+    let url = format!("{}/trash", settings.server_url);
+
+    delete_items(reqwest::Client::new().delete(&url)).await
+}
+
+// 삭제 요청을 보내고 응답의 삭제한 개수(count)를 반환
+async fn delete_items(request: reqwest::RequestBuilder) -> Result<u64, String> {
+    match request.send().await {
+        Ok(response) => {
+            match response.json::<ApiResponse<serde_json::Value>>().await {
+                Ok(api_response) => {
+                    if api_response.success {
+                        Ok(api_response.data
+                            .and_then(|data| data.get("count").and_then(|count| count.as_u64()))
+                            .unwrap_or(0))
+                    } else {
+                        Err(api_response.message)
+                    }
+                }
+                Err(e) => Err(format!("Failed to parse response: {}", e)),
+            }
+        }
+        Err(e) => Err(format!("Failed to delete items: {}", e)),
+    }
+}
+
 #[tauri::command]
 async fn show_popup_at_cursor(app: AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("main") {
@@ -407,9 +986,29 @@ pub fn run() {
         .invoke_handler(generate_handler![
             greet,
             get_clipboard_items,
+            search_items,
+            activate_clipboard_item,
+            pin_clipboard_item,
+            unpin_clipboard_item,
+            set_clipboard_item_memo,
+            delete_clipboard_item_memo,
+            update_clipboard_item,
+            delete_clipboard_item,
+            delete_clipboard_range,
+            clear_clipboard_history,
+            get_trash_items,
+            restore_trash_item,
+            delete_trash_item,
+            empty_trash,
             get_user_memos,
             add_user_memo,
             update_user_memo,
+            reorder_user_memos,
+            get_memo_folders,
+            rename_memo_folder,
+            move_user_memo,
+            get_memo_revisions,
+            restore_memo_revision,
             delete_user_memo,
             show_popup_at_cursor,
             hide_popup
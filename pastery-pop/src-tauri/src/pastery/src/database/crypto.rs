@@ -0,0 +1,228 @@
+use std::borrow::Cow;
+use argon2::Argon2;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use chacha20poly1305::aead::{Aead, AeadCore, OsRng};
+use chacha20poly1305::aead::rand_core::RngCore;
+use redb::{ReadTransaction, ReadableTable, TableDefinition, WriteTransaction};
+use sha2::{Digest, Sha256};
+use sha2::digest::generic_array::GenericArray;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+use super::record::content_hash;
+
+/*
+ * 저장 값 암호화 (ChaCha20-Poly1305)
+ * - 대상: 클립보드 레코드, 이미지, 메모, 외부 저장 파일
+ * - 키: passphrase는 Argon2id로, 키 파일은 내용을 SHA-256으로 유도하며 DB마다 임의의 salt를 섞는다.
+ * - 암호화된 값 format : ENCRYPTED_MAGIC + nonce(12) + ciphertext(인증 태그 포함)
+ *   v8 이전의 메모 테이블은 문자열 값이었으므로 ENCRYPTED_TEXT_PREFIX + base64로 저장했다 (v7에서 옮긴 메모 본문에 남아 있음).
+ *   암호화된 DB에서는 magic이 없는 값을 읽지 않는다 (평문 값을 끼워 넣어 바꾸지 못하도록).
+ * - 해시 색인의 키는 키를 섞은 해시로 바꿔 내용을 추측해 확인할 수 없게 한다.
+ * - 검색 색인은 term이 평문이므로 암호화된 DB에는 저장하지 않는다 (see search.rs).
+ * 태그/컬렉션 이름, 복사 시각, 크기 같은 메타데이터는 암호화하지 않는다.
+ * passphrase, 키 파일 내용, 유도한 키는 메모리에서 버릴 때 0으로 지운다.
+ *
+ * encryption data stored in redb
+ * format : "salt" -> 키 유도용 salt, "check" -> CHECK_PLAINTEXT를 암호화한 값 (키가 맞는지 확인)
+ * check가 있으면 암호화된 DB로 보고 잠금을 해제하기 전에는 값을 읽거나 쓰지 않는다.
+ */
+
+const ENCRYPTION_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("encryption");
+const SALT_KEY: &str = "salt";
+const CHECK_KEY: &str = "check";
+const CHECK_PLAINTEXT: &[u8] = b"pastery";
+const ENCRYPTED_MAGIC: [u8; 4] = *b"PENC";
+const ENCRYPTED_TEXT_PREFIX: &str = "\0penc:";
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+
+// 키를 유도할 재료
+#[derive(Clone, PartialEq, Debug, Zeroize, ZeroizeOnDrop)]
+pub enum EncryptionKey {
+    Passphrase(String),
+    KeyFile(String), // 키 파일 경로, 파일 내용 전체를 키 재료로 사용
+    KeyFileContents(Vec<u8>), // 키 파일 내용 (KeyFile과 같은 키를 유도)
+}
+
+#[derive(Debug)]
+pub enum EncryptionError {
+    Locked,       // 잠금 해제 전에는 다시 암호화할 수 없음
+    NotEncrypted, // 잠금 해제할 필요 없음
+    WrongKey,
+    KeyFile(std::io::Error),
+    KeyDerivation(String),
+    Undecodable(String),     // 지금 키로 읽을 수 없는 저장된 값 (다시 암호화하지 않고 중단)
+    External(std::io::Error), // 외부 저장 파일을 다시 쓰지 못함
+}
+
+impl std::fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncryptionError::Locked => write!(f, "Database is locked"),
+            EncryptionError::NotEncrypted => write!(f, "Database is not encrypted"),
+            EncryptionError::WrongKey => write!(f, "Wrong passphrase or key file"),
+            EncryptionError::KeyFile(e) => write!(f, "Failed to read key file: {}", e),
+            EncryptionError::KeyDerivation(e) => write!(f, "Failed to derive key: {}", e),
+            EncryptionError::Undecodable(value) => write!(f, "Failed to decrypt {}", value),
+            EncryptionError::External(e) => write!(f, "Failed to re-encrypt external content: {}", e),
+        }
+    }
+}
+
+fn derive_key_file(material: &[u8], salt: &[u8], derived: &mut [u8; 32]) {
+    Sha256::new().chain_update(salt).chain_update(material)
+        .finalize_into(GenericArray::from_mut_slice(derived));
+}
+
+pub(super) struct Cipher {
+    aead: ChaCha20Poly1305,
+    key: Zeroizing<[u8; 32]>,
+}
+
+impl Cipher {
+    fn derive(key: &EncryptionKey, salt: &[u8]) -> Result<Self, EncryptionError> {
+        let mut derived = Zeroizing::new([0u8; 32]);
+        match key {
+            EncryptionKey::Passphrase(passphrase) => Argon2::default()
+                .hash_password_into(passphrase.as_bytes(), salt, derived.as_mut())
+                .map_err(|e| EncryptionError::KeyDerivation(e.to_string()))?,
+            EncryptionKey::KeyFile(path) => {
+                let material = Zeroizing::new(std::fs::read(path).map_err(EncryptionError::KeyFile)?);
+                if material.is_empty() {
+                    return Err(EncryptionError::KeyDerivation(format!("Key file {} is empty", path)));
+                }
+                derive_key_file(&material, salt, &mut derived);
+            }
+            EncryptionKey::KeyFileContents(material) => {
+                if material.is_empty() {
+                    return Err(EncryptionError::KeyDerivation("Key file contents are empty".to_string()));
+                }
+                derive_key_file(material, salt, &mut derived);
+            }
+        }
+        Ok(Cipher { aead: ChaCha20Poly1305::new(Key::from_slice(derived.as_slice())), key: derived })
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self.aead.encrypt(&nonce, plaintext).expect("Failed to encrypt value");
+        [ENCRYPTED_MAGIC.as_slice(), nonce.as_slice(), &ciphertext].concat()
+    }
+
+    // 다른 키로 암호화되었거나 변조된 값이면 None
+    fn decrypt(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let body = bytes.strip_prefix(ENCRYPTED_MAGIC.as_slice())?;
+        if body.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+        self.aead.decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+    }
+}
+
+// cipher가 없으면 평문 그대로 저장
+pub(super) fn seal(cipher: Option<&Cipher>, bytes: Vec<u8>) -> Vec<u8> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(&bytes),
+        None => bytes,
+    }
+}
+
+// 암호화된 값을 cipher 없이 (잠긴 상태로) 읽거나 평문 값을 cipher로 읽으면 None
+pub(super) fn open<'a>(cipher: Option<&Cipher>, bytes: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+    match cipher {
+        Some(cipher) => cipher.decrypt(bytes).map(Cow::Owned),
+        None if bytes.starts_with(&ENCRYPTED_MAGIC) => None,
+        None => Some(Cow::Borrowed(bytes)),
+    }
+}
+
+// v8 이전 형식의 메모 값 (테스트에서 변환 전 DB를 만들 때 사용)
+#[cfg(test)]
+pub(super) fn seal_text(cipher: Option<&Cipher>, text: &str) -> String {
+    match cipher {
+        Some(cipher) => format!("{}{}", ENCRYPTED_TEXT_PREFIX, BASE64.encode(cipher.encrypt(text.as_bytes()))),
+        None => text.to_string(),
+    }
+}
+
+// v7에서 옮긴 메모 본문만 이 형식 (see migration.rs), open과 같이 cipher가 있으면 암호화된 값만 읽음
+pub(super) fn open_text(cipher: Option<&Cipher>, text: &str) -> Option<String> {
+    match (cipher, text.strip_prefix(ENCRYPTED_TEXT_PREFIX)) {
+        (Some(cipher), Some(encoded)) => String::from_utf8(cipher.decrypt(&BASE64.decode(encoded).ok()?)?).ok(),
+        (None, None) => Some(text.to_string()),
+        _ => None,
+    }
+}
+
+// HASH_INDEX_TABLE의 키: 암호화된 DB에서는 키를 섞은 해시
+pub(super) fn index_hash(cipher: Option<&Cipher>, hash: &str) -> String {
+    match cipher {
+        Some(cipher) => content_hash(&[cipher.key.as_slice(), hash.as_bytes()].concat()),
+        None => hash.to_string(),
+    }
+}
+
+pub(super) fn open_tables(write_txn: &WriteTransaction) {
+    let _ = write_txn.open_table(ENCRYPTION_TABLE).expect("Failed to open encryption table");
+}
+
+pub(super) fn is_encrypted(write_txn: &WriteTransaction) -> bool {
+    let table = write_txn.open_table(ENCRYPTION_TABLE).expect("Failed to open encryption table");
+    matches!(table.get(CHECK_KEY), Ok(Some(_)))
+}
+
+// 잠금 해제에 필요한 저장된 salt와 check 값
+// 키 유도(Argon2)는 느리므로 DB 잠금을 잡지 않고 할 수 있도록 값만 복사해 둠
+pub struct KeyCheck {
+    salt: Vec<u8>,
+    check: Vec<u8>,
+}
+
+// check 값으로 확인한 키, 유도할 때 쓴 salt가 지금 DB의 salt와 같을 때만 쓸 수 있음
+pub struct DerivedKey {
+    pub(super) cipher: Cipher,
+    pub(super) salt: Vec<u8>,
+}
+
+impl KeyCheck {
+    pub(super) fn salt(&self) -> &[u8] {
+        &self.salt
+    }
+
+    // 저장된 salt로 키를 유도하고 check 값으로 키가 맞는지 확인
+    pub fn derive(&self, key: &EncryptionKey) -> Result<DerivedKey, EncryptionError> {
+        let cipher = Cipher::derive(key, &self.salt)?;
+        match cipher.decrypt(&self.check) {
+            Some(plaintext) if plaintext == CHECK_PLAINTEXT => Ok(DerivedKey { cipher, salt: self.salt.clone() }),
+            _ => Err(EncryptionError::WrongKey),
+        }
+    }
+}
+
+// 평문 DB면 None
+pub(super) fn key_check(read_txn: &ReadTransaction) -> Option<KeyCheck> {
+    let table = read_txn.open_table(ENCRYPTION_TABLE).expect("Failed to open encryption table");
+    let read = |name: &str| table.get(name)
+        .expect("Failed to read encryption table")
+        .map(|value| value.value().to_vec());
+    Some(KeyCheck { salt: read(SALT_KEY)?, check: read(CHECK_KEY)? })
+}
+
+// 새 salt로 키를 유도해 기록 (값은 호출한 쪽에서 다시 암호화)
+pub(super) fn enable(write_txn: &WriteTransaction, key: &EncryptionKey) -> Result<Cipher, EncryptionError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = Cipher::derive(key, &salt)?;
+    let mut table = write_txn.open_table(ENCRYPTION_TABLE).expect("Failed to open encryption table");
+    table.insert(SALT_KEY, salt.as_slice()).expect("Failed to write encryption salt");
+    table.insert(CHECK_KEY, cipher.encrypt(CHECK_PLAINTEXT).as_slice()).expect("Failed to write encryption check");
+    Ok(cipher)
+}
+
+pub(super) fn disable(write_txn: &WriteTransaction) {
+    let mut table = write_txn.open_table(ENCRYPTION_TABLE).expect("Failed to open encryption table");
+    table.remove(SALT_KEY).expect("Failed to remove encryption salt");
+    table.remove(CHECK_KEY).expect("Failed to remove encryption check");
+}
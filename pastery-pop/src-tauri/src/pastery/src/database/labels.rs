@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+use redb::{ReadTransaction, ReadableTable, TableDefinition, WriteTransaction};
+use serde::Serialize;
+
+/*
+ * 클립보드 항목과 메모에 붙이는 태그와 컬렉션
+ * id는 클립보드 항목과 메모가 함께 쓰는 id이므로 두 종류 모두 같은 테이블을 쓴다.
+ *
+ * format : (name, id) -> ()  이름으로 항목 조회
+ * format : (id, name) -> ()  항목의 이름 목록 조회, 항목 삭제 시 정리
+ *
+ * 태그는 항목에 붙일 때 생기고 마지막 항목에서 떨어지면 사라진다.
+ * 컬렉션은 COLLECTION_TABLE(name -> 생성 시각 ms)에 따로 만들어 두며 비어 있어도 유지된다.
+ */
+
+const TAG_TABLE: TableDefinition<(&str, u64), ()> = TableDefinition::new("tag_item");
+const ITEM_TAG_TABLE: TableDefinition<(u64, &str), ()> = TableDefinition::new("item_tag");
+const COLLECTION_ITEM_TABLE: TableDefinition<(&str, u64), ()> = TableDefinition::new("collection_item");
+const ITEM_COLLECTION_TABLE: TableDefinition<(u64, &str), ()> = TableDefinition::new("item_collection");
+pub(super) const COLLECTION_TABLE: TableDefinition<&str, i64> = TableDefinition::new("collection");
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(super) enum Label {
+    Tag,
+    Collection,
+}
+
+type LabelTables = (TableDefinition<'static, (&'static str, u64), ()>, TableDefinition<'static, (u64, &'static str), ()>);
+
+impl Label {
+    fn tables(self) -> LabelTables {
+        match self {
+            Label::Tag => (TAG_TABLE, ITEM_TAG_TABLE),
+            Label::Collection => (COLLECTION_ITEM_TABLE, ITEM_COLLECTION_TABLE),
+        }
+    }
+}
+
+// GET /tags, GET /collections 결과 한 줄
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct LabelCount {
+    pub name: String,
+    pub count: usize,
+}
+
+// 목록/검색 결과를 태그와 컬렉션으로 거르는 조건 (태그는 모두 붙어 있어야 함)
+#[derive(Default, Clone, Debug)]
+pub struct LabelFilter {
+    pub tags: Vec<String>,
+    pub collection: Option<String>,
+}
+
+impl LabelFilter {
+    // tags: 쉼표로 구분한 태그 목록
+    pub fn parse(tags: Option<&str>, collection: Option<&str>) -> Self {
+        LabelFilter {
+            tags: tags.map(|tags| tags.split(',')
+                .map(|tag| normalize(Label::Tag, tag))
+                .filter(|tag| !tag.is_empty())
+                .collect())
+                .unwrap_or_default(),
+            collection: collection.map(|collection| normalize(Label::Collection, collection)),
+        }
+    }
+
+    pub(super) fn matches(&self, tags: &[String], collections: &[String]) -> bool {
+        self.tags.iter().all(|tag| tags.contains(tag))
+            && self.collection.as_ref().is_none_or(|collection| collections.contains(collection))
+    }
+}
+
+// 태그는 앞뒤 공백을 지우고 소문자로, 컬렉션 이름은 공백만 지움
+pub(super) fn normalize(label: Label, name: &str) -> String {
+    match label {
+        Label::Tag => name.trim().to_lowercase(),
+        Label::Collection => name.trim().to_string(),
+    }
+}
+
+pub(super) fn open_tables(write_txn: &WriteTransaction) {
+    for label in [Label::Tag, Label::Collection] {
+        let (by_name, by_item) = label.tables();
+        let _ = write_txn.open_table(by_name).expect("Failed to open label table");
+        let _ = write_txn.open_table(by_item).expect("Failed to open label table");
+    }
+    let _ = write_txn.open_table(COLLECTION_TABLE).expect("Failed to open collection table");
+}
+
+pub(super) fn add(write_txn: &WriteTransaction, label: Label, name: &str, id: u64) {
+    let (by_name, by_item) = label.tables();
+    let mut by_name = write_txn.open_table(by_name).expect("Failed to open label table");
+    let mut by_item = write_txn.open_table(by_item).expect("Failed to open label table");
+    by_name.insert((name, id), ()).expect("Failed to add label");
+    by_item.insert((id, name), ()).expect("Failed to add label");
+}
+
+// 붙어 있지 않았으면 false
+pub(super) fn remove(write_txn: &WriteTransaction, label: Label, name: &str, id: u64) -> bool {
+    let (by_name, by_item) = label.tables();
+    let mut by_name = write_txn.open_table(by_name).expect("Failed to open label table");
+    let mut by_item = write_txn.open_table(by_item).expect("Failed to open label table");
+    by_item.remove((id, name)).expect("Failed to remove label");
+    by_name.remove((name, id)).expect("Failed to remove label").is_some()
+}
+
+// 항목의 이름들을 names로 교체
+pub(super) fn set(write_txn: &WriteTransaction, label: Label, id: u64, names: &[String]) {
+    let current = names_of(&open_by_item(write_txn, label), id);
+    for name in current {
+        if !names.contains(&name) {
+            remove(write_txn, label, &name, id);
+        }
+    }
+    for name in names {
+        add(write_txn, label, name, id);
+    }
+}
+
+// 항목이 삭제될 때 붙어 있던 태그와 컬렉션을 모두 뗌
+pub(super) fn remove_item(write_txn: &WriteTransaction, id: u64) {
+    for label in [Label::Tag, Label::Collection] {
+        let names = names_of(&open_by_item(write_txn, label), id);
+        for name in names {
+            remove(write_txn, label, &name, id);
+        }
+    }
+}
+
+// 쓰기 트랜잭션에서 항목에 붙은 이름들 (휴지통으로 옮기기 전에 보관)
+pub(super) fn item_labels(write_txn: &WriteTransaction, label: Label, id: u64) -> Vec<String> {
+    names_of(&open_by_item(write_txn, label), id)
+}
+
+fn open_by_item(write_txn: &WriteTransaction, label: Label) -> redb::Table<'_, (u64, &'static str), ()> {
+    write_txn.open_table(label.tables().1).expect("Failed to open label table")
+}
+
+fn names_of(table: &impl ReadableTable<(u64, &'static str), ()>, id: u64) -> Vec<String> {
+    table.range((id, "")..(id + 1, ""))
+        .expect("Failed to read labels")
+        .filter_map(|item| item.ok())
+        .map(|(key, _)| key.value().1.to_string())
+        .collect()
+}
+
+fn ids_of(table: &impl ReadableTable<(&'static str, u64), ()>, name: &str) -> Vec<u64> {
+    table.range((name, 0)..=(name, u64::MAX))
+        .expect("Failed to read labels")
+        .filter_map(|item| item.ok())
+        .map(|(key, _)| key.value().1)
+        .collect()
+}
+
+// 항목에 붙은 이름들 (이름 순)
+pub(super) fn labels_of(read_txn: &ReadTransaction, label: Label, id: u64) -> Vec<String> {
+    let table = read_txn.open_table(label.tables().1).expect("Failed to open label table");
+    names_of(&table, id)
+}
+
+// 이름별 항목 수 (이름 순)
+pub(super) fn counts(read_txn: &ReadTransaction, label: Label) -> BTreeMap<String, usize> {
+    let table = read_txn.open_table(label.tables().0).expect("Failed to open label table");
+    let mut counts = BTreeMap::new();
+    for item in table.iter().expect("Failed to iterate labels").filter_map(|item| item.ok()) {
+        *counts.entry(item.0.value().0.to_string()).or_insert(0) += 1;
+    }
+    counts
+}
+
+// 이름이 붙은 모든 항목에서 from을 떼고 to를 붙임, 옮긴 항목 수 반환
+pub(super) fn rename(write_txn: &WriteTransaction, label: Label, from: &str, to: &str) -> usize {
+    let ids = ids_of(&write_txn.open_table(label.tables().0).expect("Failed to open label table"), from);
+    for id in &ids {
+        remove(write_txn, label, from, *id);
+        add(write_txn, label, to, *id);
+    }
+    ids.len()
+}
+
+// 이름을 모든 항목에서 뗌, 뗀 항목 수 반환
+pub(super) fn delete(write_txn: &WriteTransaction, label: Label, name: &str) -> usize {
+    let ids = ids_of(&write_txn.open_table(label.tables().0).expect("Failed to open label table"), name);
+    for id in &ids {
+        remove(write_txn, label, name, *id);
+    }
+    ids.len()
+}
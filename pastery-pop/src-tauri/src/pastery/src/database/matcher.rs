@@ -0,0 +1,195 @@
+use regex::{Regex, RegexBuilder};
+
+/*
+ * 색인을 쓰지 않고 원문을 직접 훑는 검색 방식들
+ * - fuzzy: 검색어 글자가 순서대로 나오면 일치 (fzf의 v1 알고리즘과 같은 방식으로 점수 계산)
+ * - regex: 정규식 일치, 컴파일된 크기를 제한해 잘못된 패턴이 서버를 붙잡지 않게 한다.
+ *   (regex 크레이트는 백트래킹이 없으므로 검색 시간은 원문 길이에 비례)
+ *
+ * 일치 구간은 [start, end) 문자(char) 위치로, 색인 검색의 하이라이트와 같은 형식이다.
+ */
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+// 단어 시작 (공백 다음은 조금 더 높게)
+const BONUS_BOUNDARY: i64 = SCORE_MATCH / 2;
+const BONUS_BOUNDARY_WHITE: i64 = BONUS_BOUNDARY + 2;
+const BONUS_NON_WORD: i64 = SCORE_MATCH / 2;
+// camelCase, 숫자 시작
+const BONUS_CAMEL: i64 = BONUS_BOUNDARY + SCORE_GAP_EXTENSION;
+const BONUS_CONSECUTIVE: i64 = -(SCORE_GAP_START + SCORE_GAP_EXTENSION);
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+const REGEX_DFA_SIZE_LIMIT: usize = 1 << 20;
+// 항목 하나에서 반환하는 정규식 일치 구간의 최대 개수
+const MAX_REGEX_HIGHLIGHTS: usize = 256;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CharClass {
+    White,
+    NonWord,
+    Lower,
+    Upper,
+    Letter, // 대소문자가 없는 문자 (한글 등)
+    Number,
+}
+
+fn char_class(ch: char) -> CharClass {
+    if ch.is_whitespace() {
+        CharClass::White
+    } else if ch.is_lowercase() {
+        CharClass::Lower
+    } else if ch.is_uppercase() {
+        CharClass::Upper
+    } else if ch.is_numeric() {
+        CharClass::Number
+    } else if ch.is_alphabetic() {
+        CharClass::Letter
+    } else {
+        CharClass::NonWord
+    }
+}
+
+fn bonus_for(previous: CharClass, class: CharClass) -> i64 {
+    match class {
+        CharClass::White => 0,
+        CharClass::NonWord => BONUS_NON_WORD,
+        _ => match previous {
+            CharClass::White => BONUS_BOUNDARY_WHITE,
+            CharClass::NonWord => BONUS_BOUNDARY,
+            CharClass::Lower if class == CharClass::Upper => BONUS_CAMEL,
+            previous if previous != CharClass::Number && class == CharClass::Number => BONUS_CAMEL,
+            _ => 0,
+        },
+    }
+}
+
+/**
+ * 공백으로 나눈 검색어 조각이 모두 원문에 순서대로 나오면 (점수, 일치 구간)
+ * 검색어에 대문자가 있으면 대소문자를 구분한다 (smart case).
+ */
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<(i64, Vec<[usize; 2]>)> {
+    let case_sensitive = pattern.chars().any(char::is_uppercase);
+    let mut score = 0;
+    let mut positions = Vec::new();
+    let mut matched = false;
+    for term in pattern.split_whitespace() {
+        let (term_score, term_positions) = fuzzy_match_term(term, text, case_sensitive)?;
+        score += term_score;
+        positions.extend(term_positions);
+        matched = true;
+    }
+    if !matched {
+        return None;
+    }
+    positions.sort_unstable();
+    positions.dedup();
+    Some((score, to_spans(&positions)))
+}
+
+fn fuzzy_match_term(term: &str, text: &str, case_sensitive: bool) -> Option<(i64, Vec<usize>)> {
+    let fold = |ch: char| if case_sensitive { ch } else { ch.to_lowercase().next().unwrap_or(ch) };
+    let pattern: Vec<char> = term.chars().map(fold).collect();
+
+    // 앞에서부터 검색어 글자를 순서대로 찾아 일치가 끝나는 위치를 구함
+    let mut start = None;
+    let mut end = 0;
+    let mut index = 0;
+    for (position, ch) in text.chars().enumerate() {
+        if fold(ch) == pattern[index] {
+            start.get_or_insert(position);
+            index += 1;
+            if index == pattern.len() {
+                end = position + 1;
+                break;
+            }
+        }
+    }
+    let mut start = start?;
+    if index < pattern.len() {
+        return None;
+    }
+
+    // 끝에서 거꾸로 찾아 가장 짧은 구간으로 좁힘
+    let chars: Vec<char> = text.chars().take(end).collect();
+    let mut index = pattern.len() - 1;
+    for position in (start..end).rev() {
+        if fold(chars[position]) == pattern[index] {
+            if index == 0 {
+                start = position;
+                break;
+            }
+            index -= 1;
+        }
+    }
+
+    let mut score = 0;
+    let mut positions = Vec::new();
+    let mut in_gap = false;
+    let mut consecutive = 0;
+    let mut first_bonus = 0;
+    let mut previous = if start == 0 { CharClass::White } else { char_class(chars[start - 1]) };
+    for (position, &ch) in chars.iter().enumerate().take(end).skip(start) {
+        let class = char_class(ch);
+        if positions.len() < pattern.len() && fold(ch) == pattern[positions.len()] {
+            let mut bonus = bonus_for(previous, class);
+            if consecutive == 0 {
+                first_bonus = bonus;
+            } else {
+                // 연속 일치는 덩어리 첫 글자의 보너스를 이어받음
+                if bonus >= BONUS_BOUNDARY && bonus > first_bonus {
+                    first_bonus = bonus;
+                }
+                bonus = bonus.max(first_bonus).max(BONUS_CONSECUTIVE);
+            }
+            score += SCORE_MATCH + if positions.is_empty() { bonus * BONUS_FIRST_CHAR_MULTIPLIER } else { bonus };
+            positions.push(position);
+            in_gap = false;
+            consecutive += 1;
+        } else {
+            score += if in_gap { SCORE_GAP_EXTENSION } else { SCORE_GAP_START };
+            in_gap = true;
+            consecutive = 0;
+            first_bonus = 0;
+        }
+        previous = class;
+    }
+    Some((score, positions))
+}
+
+// 정렬된 문자 위치들을 연속 구간으로 묶음
+fn to_spans(positions: &[usize]) -> Vec<[usize; 2]> {
+    let mut spans: Vec<[usize; 2]> = Vec::new();
+    for &position in positions {
+        match spans.last_mut() {
+            Some(span) if span[1] == position => span[1] = position + 1,
+            _ => spans.push([position, position + 1]),
+        }
+    }
+    spans
+}
+
+pub fn compile_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_DFA_SIZE_LIMIT)
+        .build()
+}
+
+// 빈 문자열 일치를 제외한 일치 구간 (문자 위치)
+pub fn regex_highlights(regex: &Regex, text: &str) -> Vec<[usize; 2]> {
+    let mut spans = Vec::new();
+    // 바이트 위치를 문자 위치로 바꾸기 위해 앞에서부터 누적
+    let mut byte_position = 0;
+    let mut char_position = 0;
+    for found in regex.find_iter(text).filter(|found| !found.is_empty()).take(MAX_REGEX_HIGHLIGHTS) {
+        char_position += text[byte_position..found.start()].chars().count();
+        let start = char_position;
+        char_position += found.as_str().chars().count();
+        byte_position = found.end();
+        spans.push([start, char_position]);
+    }
+    spans
+}
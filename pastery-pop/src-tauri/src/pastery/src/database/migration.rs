@@ -0,0 +1,420 @@
+use log::{info, warn};
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition, TableHandle, WriteTransaction};
+use super::{now_millis, ClipboardRecord};
+
+/*
+ * clip.db 스키마 버전 관리
+ * METADATA_TABLE의 "schema_version" 값으로 현재 레이아웃을 판단하고,
+ * 이전 버전이면 파일을 백업한 뒤 MIGRATIONS를 순서대로 적용한다.
+ *
+ * v0: 문자열 키 "clipboard-YYYY-MM-DD-sequence" -> 본문 (이미지/HTML/파일 목록은 별도 테이블)
+ * v1: (date, sequence) 키 -> 직렬화된 ClipboardRecord
+ * v2: 클립보드와 메모가 함께 쓰는 u64 id 키 (다음 id는 METADATA_TABLE의 "next_id")
+ * v3: (timestamp, id) 시간 인덱스 추가
+ * v4: 내용 해시 -> id 인덱스 추가 (중복 복사 감지)
+ * v5: 고정된 항목의 id 목록 추가
+ * v6: 저장된 항목 크기의 합을 METADATA_TABLE의 "total_bytes"에 기록
+ * v7: 메모의 만든/수정한 시각과 사용자가 정한 순서 추가
+ * v8: 메모 값을 본문 문자열에서 직렬화된 MemoRecord (제목, 폴더, 설명 포함)로 변환
+ *
+ * 각 단계는 그 시점의 테이블 정의를 직접 사용하므로 mod.rs의 정의가 바뀌어도 영향받지 않는다.
+ */
+
+pub(super) const METADATA_TABLE: TableDefinition<&str, u64> = TableDefinition::new("metadata");
+pub(super) const SCHEMA_VERSION_KEY: &str = "schema_version";
+pub(super) const NEXT_ID_KEY: &str = "next_id";
+pub(super) const TOTAL_BYTES_KEY: &str = "total_bytes";
+pub(super) const CURRENT_SCHEMA_VERSION: u64 = 8;
+// v7에서 옮긴 메모 값: V7_MEMO_MAGIC + 이전 본문 문자열 (직렬화된 MemoRecord와 구분)
+pub(super) const V7_MEMO_MAGIC: [u8; 4] = *b"PMV7";
+
+// MIGRATIONS[n]은 버전 n을 n + 1로 변환
+type Migration = fn(&WriteTransaction);
+const MIGRATIONS: [Migration; CURRENT_SCHEMA_VERSION as usize] = [
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+];
+
+// v0 테이블
+pub(super) const V0_CLIPBOARD_TABLE: TableDefinition<&str, &str> = TableDefinition::new("clipboard");
+pub(super) const V0_IMAGE_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("clipboard_image");
+pub(super) const V0_HTML_TABLE: TableDefinition<&str, &str> = TableDefinition::new("clipboard_html");
+pub(super) const V0_FILES_TABLE: TableDefinition<&str, &str> = TableDefinition::new("clipboard_files");
+
+// v1 테이블 (메모 테이블은 v0과 동일)
+pub(super) const V1_CLIPBOARD_TABLE: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("clipboard_record");
+pub(super) const V1_IMAGE_TABLE: TableDefinition<(&str, u64), &[u8]> = TableDefinition::new("clipboard_png");
+pub(super) const V1_MEMO_TABLE: TableDefinition<&str, &str> = TableDefinition::new("memo");
+
+// v2 테이블
+const V2_CLIPBOARD_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("clipboard_item");
+const V2_IMAGE_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("clipboard_item_png");
+const V2_MEMO_TABLE: TableDefinition<u64, &str> = TableDefinition::new("memo_item");
+
+// v3 테이블
+const V3_TIME_INDEX_TABLE: TableDefinition<(i64, u64), ()> = TableDefinition::new("clipboard_by_time");
+
+// v4 테이블
+const V4_HASH_INDEX_TABLE: TableDefinition<&str, u64> = TableDefinition::new("clipboard_by_hash");
+
+// v5 테이블
+const V5_PINNED_TABLE: TableDefinition<u64, ()> = TableDefinition::new("clipboard_pinned");
+
+// v7 테이블
+const V7_MEMO_META_TABLE: TableDefinition<u64, (i64, i64, i64)> = TableDefinition::new("memo_meta");
+
+// v8 테이블
+const V8_MEMO_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("memo_record");
+
+// 필요하면 백업 후 마이그레이션을 적용하고 (다시 연) 데이터베이스를 반환
+pub(super) fn run(path: &str, db: Database) -> Database {
+    let version = match detect_version(&db) {
+        Some(version) => version,
+        None => {
+            // 새 데이터베이스는 바로 현재 버전으로 기록
+            let write_txn = db.begin_write().expect("Failed to begin write transaction");
+            set_version(&write_txn, CURRENT_SCHEMA_VERSION);
+            write_txn.commit().expect("Failed to commit transaction");
+            return db;
+        }
+    };
+
+    if version == CURRENT_SCHEMA_VERSION {
+        return db;
+    }
+    if version > CURRENT_SCHEMA_VERSION {
+        panic!("Database at {} uses schema v{}, but this build only supports up to v{}",
+            path, version, CURRENT_SCHEMA_VERSION);
+    }
+
+    // 파일 잠금을 풀기 위해 닫은 뒤 백업
+    drop(db);
+    let backup = backup_path(path, version);
+    std::fs::copy(path, &backup)
+        .unwrap_or_else(|e| panic!("Failed to back up database to {}: {}", backup, e));
+    info!("Backed up schema v{} database to {}", version, backup);
+
+    let db = Database::create(path)
+        .unwrap_or_else(|e| panic!("Failed to reopen database at {}: {}", path, e));
+    migrate(&db, version);
+    db
+}
+
+// from 버전에서 현재 버전까지 단계별로 적용
+// 한 트랜잭션 안에서 만든 테이블을 다시 삭제할 수 없으므로 단계마다 커밋하고 버전을 기록한다.
+pub(super) fn migrate(db: &Database, from: u64) {
+    for version in from..CURRENT_SCHEMA_VERSION {
+        info!("Migrating database schema v{} -> v{}", version, version + 1);
+        let write_txn = db.begin_write().expect("Failed to begin write transaction");
+        MIGRATIONS[version as usize](&write_txn);
+        set_version(&write_txn, version + 1);
+        write_txn.commit().expect("Failed to commit migration");
+    }
+}
+
+// 테이블이 하나도 없으면 None (새 데이터베이스)
+pub(super) fn detect_version(db: &Database) -> Option<u64> {
+    let read_txn = db.begin_read().expect("Failed to begin read transaction");
+    let tables: Vec<String> = read_txn.list_tables()
+        .expect("Failed to list tables")
+        .map(|table| table.name().to_string())
+        .collect();
+
+    if tables.iter().any(|name| name == METADATA_TABLE.name()) {
+        let table = read_txn.open_table(METADATA_TABLE).expect("Failed to open metadata table");
+        if let Some(version) = table.get(SCHEMA_VERSION_KEY).expect("Failed to read schema version") {
+            return Some(version.value());
+        }
+    }
+
+    if tables.is_empty() {
+        None
+    } else if tables.iter().any(|name| name == V0_CLIPBOARD_TABLE.name()) {
+        Some(0)
+    } else {
+        // 버전 기록이 도입되기 전의 레코드 레이아웃
+        warn!("Database has no schema version; assuming v1");
+        Some(1)
+    }
+}
+
+fn set_version(write_txn: &WriteTransaction, version: u64) {
+    let mut table = write_txn.open_table(METADATA_TABLE).expect("Failed to open metadata table");
+    table.insert(SCHEMA_VERSION_KEY, version).expect("Failed to write schema version");
+}
+
+fn backup_path(path: &str, version: u64) -> String {
+    let stamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+    format!("{}.v{}-{}.bak", path, version, stamp)
+}
+
+fn table_exists(write_txn: &WriteTransaction, name: &str) -> bool {
+    write_txn.list_tables()
+        .expect("Failed to list tables")
+        .any(|table| table.name() == name)
+}
+
+// v0 -> v1: 문자열 키 파싱 대신 (date, sequence) 키와 ClipboardRecord 값으로 변환
+fn migrate_v0_to_v1(write_txn: &WriteTransaction) {
+    if !table_exists(write_txn, V0_CLIPBOARD_TABLE.name()) {
+        return;
+    }
+
+    let mut converted = 0;
+    {
+        let legacy_table = write_txn.open_table(V0_CLIPBOARD_TABLE).expect("Failed to open v0 clipboard table");
+        let legacy_image_table = write_txn.open_table(V0_IMAGE_TABLE).expect("Failed to open v0 image table");
+        let legacy_html_table = write_txn.open_table(V0_HTML_TABLE).expect("Failed to open v0 html table");
+        let legacy_files_table = write_txn.open_table(V0_FILES_TABLE).expect("Failed to open v0 files table");
+        let mut table = write_txn.open_table(V1_CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+        let mut image_table = write_txn.open_table(V1_IMAGE_TABLE).expect("Failed to open image table");
+
+        for item in legacy_table.iter().expect("Failed to iterate v0 clipboard table") {
+            let (key, value) = item.expect("Failed to read v0 item");
+            let key_str = key.value();
+            // 키 형식: "clipboard-YYYY-MM-DD-sequence"에서 날짜와 시퀀스 분리
+            let Some((date, sequence)) = key_str.strip_prefix("clipboard-")
+                .and_then(|rest| rest.rsplit_once('-'))
+                .and_then(|(date, sequence)| Some((date, sequence.parse::<u64>().ok()?)))
+            else {
+                warn!("Skipping unrecognized v0 clipboard key: {}", key_str);
+                continue;
+            };
+
+            let timestamp = legacy_timestamp(date, sequence);
+            let content = value.value();
+            let files = legacy_files_table.get(key_str).expect("Failed to read v0 file list")
+                .and_then(|files| serde_json::from_str::<Vec<String>>(files.value()).ok());
+            let html = legacy_html_table.get(key_str).expect("Failed to read v0 html")
+                .map(|html| html.value().to_string());
+            let png = legacy_image_table.get(key_str).expect("Failed to read v0 image")
+                .map(|png| png.value().to_vec());
+
+            let record = match (files, html, &png) {
+                (Some(files), _, _) => ClipboardRecord::files(timestamp, &files),
+                (_, _, Some(png)) => ClipboardRecord::image(timestamp, content, png),
+                (_, Some(html), _) => ClipboardRecord::html(timestamp, content, &html),
+                _ => ClipboardRecord::text(timestamp, content),
+            };
+            // v1 레코드는 JSON 그대로 저장
+            let bytes = serde_json::to_vec(&record).expect("Failed to serialize clipboard record");
+            table.insert((date, sequence), bytes.as_slice())
+                .expect("Failed to insert converted clipboard record");
+            if let Some(png) = png {
+                image_table.insert((date, sequence), png.as_slice())
+                    .expect("Failed to insert converted image data");
+            }
+            converted += 1;
+        }
+    }
+
+    write_txn.delete_table(V0_CLIPBOARD_TABLE).expect("Failed to delete v0 clipboard table");
+    write_txn.delete_table(V0_IMAGE_TABLE).expect("Failed to delete v0 image table");
+    write_txn.delete_table(V0_HTML_TABLE).expect("Failed to delete v0 html table");
+    write_txn.delete_table(V0_FILES_TABLE).expect("Failed to delete v0 files table");
+    info!("Converted {} v0 clipboard items to records", converted);
+}
+
+// v1 -> v2: (date, sequence)와 "memo-N" 키를 하나의 u64 id 공간으로 통합
+// 클립보드 항목은 시간 순서대로, 그 다음 메모를 시퀀스 순서대로 번호를 매긴다.
+fn migrate_v1_to_v2(write_txn: &WriteTransaction) {
+    let mut next_id = 1u64;
+    {
+        let v1_table = write_txn.open_table(V1_CLIPBOARD_TABLE).expect("Failed to open v1 clipboard table");
+        let v1_image_table = write_txn.open_table(V1_IMAGE_TABLE).expect("Failed to open v1 image table");
+        let v1_memo_table = write_txn.open_table(V1_MEMO_TABLE).expect("Failed to open v1 memo table");
+        let mut table = write_txn.open_table(V2_CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+        let mut image_table = write_txn.open_table(V2_IMAGE_TABLE).expect("Failed to open image table");
+        let mut memo_table = write_txn.open_table(V2_MEMO_TABLE).expect("Failed to open memo table");
+
+        // v1 키는 (날짜, 시퀀스) 순으로 정렬되어 있으므로 순회 순서가 곧 시간 순서
+        for item in v1_table.iter().expect("Failed to iterate v1 clipboard table") {
+            let (key, value) = item.expect("Failed to read v1 item");
+            let (date, sequence) = key.value();
+            table.insert(next_id, value.value()).expect("Failed to insert converted clipboard record");
+            if let Some(png) = v1_image_table.get((date, sequence)).expect("Failed to read v1 image") {
+                image_table.insert(next_id, png.value()).expect("Failed to insert converted image data");
+            }
+            next_id += 1;
+        }
+
+        let mut memos: Vec<(u64, String)> = v1_memo_table.iter()
+            .expect("Failed to iterate v1 memo table")
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, value)| {
+                let sequence = key.value().strip_prefix("memo-")?.parse::<u64>().ok()?;
+                Some((sequence, value.value().to_string()))
+            })
+            .collect();
+        memos.sort_by_key(|(sequence, _)| *sequence);
+        for (_, memo) in &memos {
+            memo_table.insert(next_id, memo.as_str()).expect("Failed to insert converted memo");
+            next_id += 1;
+        }
+
+        let mut metadata = write_txn.open_table(METADATA_TABLE).expect("Failed to open metadata table");
+        metadata.insert(NEXT_ID_KEY, next_id).expect("Failed to write next id");
+    }
+
+    write_txn.delete_table(V1_CLIPBOARD_TABLE).expect("Failed to delete v1 clipboard table");
+    write_txn.delete_table(V1_IMAGE_TABLE).expect("Failed to delete v1 image table");
+    write_txn.delete_table(V1_MEMO_TABLE).expect("Failed to delete v1 memo table");
+    info!("Assigned ids to {} clipboard items and memos", next_id - 1);
+}
+
+#[derive(serde::Deserialize)]
+struct V2RecordTime {
+    timestamp: i64,
+}
+
+// v2 -> v3: 기존 레코드의 timestamp로 시간 인덱스 생성
+fn migrate_v2_to_v3(write_txn: &WriteTransaction) {
+    let table = write_txn.open_table(V2_CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+    let mut index = write_txn.open_table(V3_TIME_INDEX_TABLE).expect("Failed to open time index table");
+
+    let mut indexed = 0;
+    for item in table.iter().expect("Failed to iterate clipboard table") {
+        let (key, value) = item.expect("Failed to read clipboard item");
+        // v2 레코드는 JSON이며 여기서는 timestamp만 필요
+        let Ok(record) = serde_json::from_slice::<V2RecordTime>(value.value()) else {
+            warn!("Skipping unreadable clipboard record: {}", key.value());
+            continue;
+        };
+        index.insert((record.timestamp, key.value()), ()).expect("Failed to update time index");
+        indexed += 1;
+    }
+    info!("Indexed {} clipboard items by time", indexed);
+}
+
+#[derive(serde::Deserialize)]
+struct V3RecordHash {
+    hash: String,
+}
+
+// v3 -> v4: 기존 레코드의 해시로 해시 인덱스 생성
+// 같은 내용이 여러 번 저장되어 있으면 가장 최근 항목을 가리키도록 시간 순으로 덮어쓴다.
+fn migrate_v3_to_v4(write_txn: &WriteTransaction) {
+    let table = write_txn.open_table(V2_CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+    let time_index = write_txn.open_table(V3_TIME_INDEX_TABLE).expect("Failed to open time index table");
+    let mut index = write_txn.open_table(V4_HASH_INDEX_TABLE).expect("Failed to open hash index table");
+
+    let mut indexed = 0;
+    for item in time_index.iter().expect("Failed to iterate time index") {
+        let (key, _) = item.expect("Failed to read time index");
+        let (_, id) = key.value();
+        let Some(value) = table.get(id).expect("Failed to read clipboard item") else {
+            continue;
+        };
+        let Ok(record) = serde_json::from_slice::<V3RecordHash>(value.value()) else {
+            warn!("Skipping unreadable clipboard record: {}", id);
+            continue;
+        };
+        index.insert(record.hash.as_str(), id).expect("Failed to update hash index");
+        indexed += 1;
+    }
+    info!("Indexed {} clipboard items by content hash", indexed);
+}
+
+#[derive(serde::Deserialize)]
+struct V4RecordPinned {
+    #[serde(default)]
+    pinned: bool,
+}
+
+// v4 -> v5: 레코드의 pinned 플래그로 고정 항목 목록 생성
+fn migrate_v4_to_v5(write_txn: &WriteTransaction) {
+    let table = write_txn.open_table(V2_CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+    let mut pinned_table = write_txn.open_table(V5_PINNED_TABLE).expect("Failed to open pinned table");
+
+    let mut pinned = 0;
+    for item in table.iter().expect("Failed to iterate clipboard table") {
+        let (key, value) = item.expect("Failed to read clipboard item");
+        let Ok(record) = serde_json::from_slice::<V4RecordPinned>(value.value()) else {
+            warn!("Skipping unreadable clipboard record: {}", key.value());
+            continue;
+        };
+        if record.pinned {
+            pinned_table.insert(key.value(), ()).expect("Failed to update pinned table");
+            pinned += 1;
+        }
+    }
+    info!("Found {} pinned clipboard items", pinned);
+}
+
+#[derive(serde::Deserialize)]
+struct V5RecordSize {
+    size: u64,
+}
+
+// v5 -> v6: 레코드의 size를 더해 전체 크기 기록
+fn migrate_v5_to_v6(write_txn: &WriteTransaction) {
+    let table = write_txn.open_table(V2_CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+    let mut metadata = write_txn.open_table(METADATA_TABLE).expect("Failed to open metadata table");
+
+    let mut total = 0;
+    for item in table.iter().expect("Failed to iterate clipboard table") {
+        let (key, value) = item.expect("Failed to read clipboard item");
+        let Ok(record) = serde_json::from_slice::<V5RecordSize>(value.value()) else {
+            warn!("Skipping unreadable clipboard record: {}", key.value());
+            continue;
+        };
+        total += record.size;
+    }
+    metadata.insert(TOTAL_BYTES_KEY, total).expect("Failed to write total bytes");
+    info!("Clipboard items use {} bytes", total);
+}
+
+// v6 -> v7: 메모마다 (만든 시각, 수정한 시각, 순서) 기록
+// 이전에는 메모의 시각을 저장하지 않았으므로 변환 시각으로 채우고, 순서는 기존 목록(id 내림차순)을 따른다.
+// 메모 값은 읽지 않으므로 암호화된 DB도 그대로 변환된다.
+fn migrate_v6_to_v7(write_txn: &WriteTransaction) {
+    let memo_table = write_txn.open_table(V2_MEMO_TABLE).expect("Failed to open memo table");
+    let mut meta_table = write_txn.open_table(V7_MEMO_META_TABLE).expect("Failed to open memo meta table");
+
+    let now = now_millis();
+    let ids: Vec<u64> = memo_table.iter()
+        .expect("Failed to iterate memo table")
+        .rev()
+        .filter_map(|item| item.ok())
+        .map(|(key, _)| key.value())
+        .collect();
+    for (position, id) in ids.iter().enumerate() {
+        meta_table.insert(*id, (now, now, position as i64)).expect("Failed to write memo meta");
+    }
+    info!("Recorded timestamps for {} memos", ids.len());
+}
+
+// v7 -> v8: 메모 본문을 새 테이블로 옮기고 이전 테이블 삭제
+// 암호화된 DB는 키 없이 변환하므로 본문 문자열 앞에 V7_MEMO_MAGIC을 붙여 그대로 옮기고,
+// 읽을 때 MemoRecord::decode가 레코드로 바꾸며 다음에 저장하거나 rekey할 때 레코드 전체가 암호화된다.
+fn migrate_v7_to_v8(write_txn: &WriteTransaction) {
+    let mut converted = 0;
+    {
+        let legacy_table = write_txn.open_table(V2_MEMO_TABLE).expect("Failed to open v7 memo table");
+        let mut table = write_txn.open_table(V8_MEMO_TABLE).expect("Failed to open memo table");
+        for item in legacy_table.iter().expect("Failed to iterate v7 memo table") {
+            let (key, value) = item.expect("Failed to read memo");
+            let record = [V7_MEMO_MAGIC.as_slice(), value.value().as_bytes()].concat();
+            table.insert(key.value(), record.as_slice()).expect("Failed to insert memo");
+            converted += 1;
+        }
+    }
+    write_txn.delete_table(V2_MEMO_TABLE).expect("Failed to delete v7 memo table");
+    info!("Converted {} memos to records", converted);
+}
+
+// v0은 날짜만 저장했으므로 그날 자정 + 시퀀스(ms)로 순서를 보존
+pub(super) fn legacy_timestamp(date: &str, sequence: u64) -> i64 {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .and_then(|midnight| midnight.and_local_timezone(chrono::Local).earliest())
+        .map(|midnight| midnight.timestamp_millis() + sequence as i64)
+        .unwrap_or(sequence as i64)
+}
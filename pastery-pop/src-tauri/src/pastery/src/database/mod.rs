@@ -1,42 +1,326 @@
 
-use log::info;
-use redb::{Database, TableDefinition, ReadableTable, ReadableDatabase};
+use std::ops::Bound;
+use log::{info, warn, error};
+use redb::{Database, TableDefinition, ReadableTable, ReadableTableMetadata, ReadableDatabase, ReadTransaction, WriteTransaction};
 use serde::{Serialize, Deserialize};
 
+mod crypto;
+mod labels;
+mod matcher;
+mod migration;
+mod query;
+mod record;
+mod revision;
+mod search;
+mod storage;
+mod tokenizer;
+mod trash;
+
+pub use crypto::{DerivedKey, EncryptionError, EncryptionKey, KeyCheck};
+use crypto::Cipher;
+pub use labels::{LabelCount, LabelFilter};
+use labels::Label;
+pub use query::{ClipboardPage, ClipboardQuery, Cursor, MemoQuery, MemoSort, PinnedFilter};
+pub use record::{normalize_folder, ClipboardRecord, MemoRecord};
+pub use revision::MemoRevision;
+pub use search::{SearchHit, SearchResults};
+pub use storage::{OversizePolicy, SizeLimits, StorageStats};
+pub use tokenizer::{HangulTokenizer, Tokenizer};
+pub use trash::TrashItem;
+use trash::{TrashEntry, TrashedItem};
+
 /**
  * clipboard data stored in redb
- * format : clipboard-date-time-sequence -> clipboard_content
- * key format: "clipboard-YYYY-MM-DD-sequence" (e.g., "clipboard-2025-08-10-1", "clipboard-2025-08-10-2")
+ * format : id -> serialized ClipboardRecord
+ * id는 클립보드 항목과 메모가 함께 쓰는 단조 증가 u64 (e.g., 1, 2, 3)
+ * 날짜는 키가 아니라 레코드의 timestamp에서 계산한다.
+ *
+ * image clipboard data stored in redb
+ * format : id -> PNG bytes (same key as CLIPBOARD_TABLE)
+ *
+ * clipboard time index stored in redb
+ * format : (timestamp, id) -> ()
+ * 복사 시각 순으로 정렬되어 있으므로 최신 N개 조회와 오래된 항목 삭제는 range 조회로 처리한다.
+ *
+ * clipboard hash index stored in redb
+ * format : content hash -> id (같은 내용이 다시 복사되었는지 확인, DedupMode::Global)
+ *
+ * pinned clipboard items stored in redb
+ * format : id -> () (고정된 항목은 max_items 정리 대상에서 제외)
+ *
+ * clipboard expiry index stored in redb
+ * format : (expires_at, id) -> () (만료 시각이 있는 항목만, 비밀 정보로 감지되어 짧은 만료로 저장된 항목)
+ *
+ * tags and collections stored in redb (see labels.rs)
+ * format : (name, id) -> (), (id, name) -> ()
  * 
  * memo data stored in redb
- * format : memo-sequence -> memo_content
- * key format: "memo-sequence" (e.g., "memo-1", "memo-2")
+ * format : id -> serialized MemoRecord (본문과 선택 항목인 제목, 폴더 경로, 설명)
+ * 폴더는 따로 저장하지 않고 메모의 폴더 경로에서 모은다 (메모가 없는 폴더는 없음).
+ *
+ * memo timestamps and order stored in redb
+ * format : id -> (created_at, updated_at, position) (position은 사용자가 정한 순서, 작을수록 위)
+ * format : "top_position" -> 가장 위 메모의 position (see next_top_position)
+ *
+ * memo revisions stored in redb (see revision.rs)
+ * format : (memo id, revision) -> 수정하기 전 메모 내용
+ *
+ * memos attached to clipboard items stored in redb
+ * format : memo id -> clipboard item id (항목 쪽은 ClipboardRecord::memo_id)
+ * 항목이 정리되어도 메모는 지워지지 않고 일반 메모로 남는다.
+ *
+ * trash stored in redb (see trash.rs)
+ * format : id -> 삭제한 클립보드 레코드나 메모, 태그와 컬렉션 (복원하면 같은 id로 돌아감)
+ *
+ * search index stored in redb (see search.rs)
+ * format : (term, id) -> term count
+ *
+ * encryption salt and key check stored in redb (see crypto.rs)
+ * 암호화된 DB에서는 클립보드 레코드, 이미지, 메모 값이 암호화되고 검색 색인은 저장하지 않는다.
+ *
+ * metadata stored in redb (see migration.rs)
+ * format : "schema_version" -> u64, "next_id" -> u64, "search_index_version" -> u64, "total_bytes" -> u64
+ *
+ * max_item_bytes를 넘어 외부 저장된 항목은 DB 파일 옆 "<db 파일>.blobs" 디렉터리에 저장 (see storage.rs)
  */
 
-const CLIPBOARD_TABLE: TableDefinition<&str, &str> = TableDefinition::new("clipboard");
-const MEMO_TABLE: TableDefinition<&str, &str> = TableDefinition::new("memo");
+const CLIPBOARD_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("clipboard_item");
+const MEMO_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("memo_record");
+const IMAGE_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("clipboard_item_png");
+const TIME_INDEX_TABLE: TableDefinition<(i64, u64), ()> = TableDefinition::new("clipboard_by_time");
+const HASH_INDEX_TABLE: TableDefinition<&str, u64> = TableDefinition::new("clipboard_by_hash");
+const PINNED_TABLE: TableDefinition<u64, ()> = TableDefinition::new("clipboard_pinned");
+const MEMO_LINK_TABLE: TableDefinition<u64, u64> = TableDefinition::new("memo_item_link");
+const EXPIRY_INDEX_TABLE: TableDefinition<(i64, u64), ()> = TableDefinition::new("clipboard_by_expiry");
+const MEMO_META_TABLE: TableDefinition<u64, (i64, i64, i64)> = TableDefinition::new("memo_meta");
+const MEMO_ORDER_TABLE: TableDefinition<&str, i64> = TableDefinition::new("memo_order");
+const TOP_POSITION_KEY: &str = "top_position";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ClipboardKind {
+    Text,
+    Image,
+    Files,
+}
+
+impl std::str::FromStr for ClipboardKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "text" => Ok(ClipboardKind::Text),
+            "image" => Ok(ClipboardKind::Image),
+            "files" => Ok(ClipboardKind::Files),
+            _ => Err(format!("Unknown clipboard kind: {}", kind)),
+        }
+    }
+}
+
+// 이미 저장된 내용을 다시 복사했을 때의 처리
+// 중복이면 새 항목을 만들지 않고 기존 항목을 최신으로 올리고 사용 횟수를 늘린다.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupMode {
+    Off,         // 항상 새 항목으로 저장
+    Consecutive, // 가장 최근 항목과 같을 때만 중복
+    #[default]
+    Global,      // 기록 전체에서 같은 내용을 찾음
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct FileEntry {
+    pub path: String,
+    pub exists: bool, // 조회 시점에 파일이 아직 존재하는지
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ClipboardItem {
+    pub id: u64,
     pub date: String,
-    pub sequence: u64,
+    pub timestamp: i64,
+    pub size: u64,
+    pub kind: ClipboardKind,
+    pub hash: String,
+    pub pinned: bool,
+    pub memo_id: Option<u64>,
+    pub memo: Option<String>,
+    pub use_count: u64,
+    pub external: bool,
+    pub sensitive: bool,         // popup에서 내용을 가림
+    pub expires_at: Option<i64>,
     pub content: String,
+    pub html: Option<String>,
+    pub files: Option<Vec<FileEntry>>,
+    pub tags: Vec<String>,
+    pub collections: Vec<String>,
+}
+
+impl ClipboardItem {
+    fn from_record(id: u64, record: ClipboardRecord) -> Self {
+        // 파일 목록은 조회 시점에 존재 여부를 확인
+        let files = record.files.map(|paths| paths.into_iter()
+            .map(|path| FileEntry {
+                exists: std::path::Path::new(&path).exists(),
+                path,
+            })
+            .collect());
+        ClipboardItem {
+            id,
+            date: local_date(record.timestamp),
+            timestamp: record.timestamp,
+            size: record.size,
+            kind: record.kind,
+            hash: record.hash,
+            pinned: record.pinned,
+            memo_id: record.memo_id,
+            memo: None,
+            use_count: record.use_count,
+            external: record.external,
+            sensitive: record.sensitive,
+            expires_at: record.expires_at,
+            content: record.content,
+            html: record.html,
+            files,
+            tags: Vec::new(),
+            collections: Vec::new(),
+        }
+    }
+
+    // 항목에 붙은 메모, 태그와 컬렉션을 채움
+    fn with_annotations(mut self, read_txn: &ReadTransaction, cipher: Option<&Cipher>) -> Self {
+        if let Some(memo_id) = self.memo_id {
+            let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+            self.memo = memo_table.get(memo_id)
+                .expect("Failed to get memo")
+                .and_then(|value| MemoRecord::decode(value.value(), cipher))
+                .map(|record| record.memo);
+        }
+        self.tags = labels::labels_of(read_txn, Label::Tag, self.id);
+        self.collections = labels::labels_of(read_txn, Label::Collection, self.id);
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct MemoItem {
-    pub date: String,
-    pub sequence: u64,
-    pub memo: String,
+    pub date: String,         // 만든 날짜
+    pub id: u64,
+    pub memo: String,         // 붙여넣을 본문
+    pub title: Option<String>,
+    pub folder: Option<String>,
+    pub description: Option<String>,
+    pub item_id: Option<u64>, // 메모가 붙어 있는 클립보드 항목
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub position: i64,        // 사용자가 정한 순서 (sort=manual)
+    pub tags: Vec<String>,
+    pub collections: Vec<String>,
+}
+
+impl MemoItem {
+    fn read(read_txn: &ReadTransaction, id: u64, record: MemoRecord) -> Self {
+        let link_table = read_txn.open_table(MEMO_LINK_TABLE).expect("Failed to open memo link table");
+        let meta_table = read_txn.open_table(MEMO_META_TABLE).expect("Failed to open memo meta table");
+        let meta = meta_table.get(id).expect("Failed to read memo meta")
+            .map_or(MemoMeta::default(), |value| MemoMeta::from_value(value.value()));
+        MemoItem {
+            date: local_date(meta.created_at),
+            id,
+            memo: record.memo,
+            title: record.title,
+            folder: record.folder,
+            description: record.description,
+            created_at: meta.created_at,
+            updated_at: meta.updated_at,
+            position: meta.position,
+            item_id: link_table.get(id).expect("Failed to read memo link").map(|value| value.value()),
+            tags: labels::labels_of(read_txn, Label::Tag, id),
+            collections: labels::labels_of(read_txn, Label::Collection, id),
+        }
+    }
+}
+
+/**
+ * 메모 수정 내용 (PUT /memo)
+ * None인 항목은 그대로 두고, title/folder/description을 빈 문자열로 주면 지운다.
+ */
+#[derive(Default, Clone, Debug)]
+pub struct MemoEdit {
+    pub memo: Option<String>,
+    pub title: Option<String>,
+    pub folder: Option<String>,
+    pub description: Option<String>,
+}
+
+impl MemoEdit {
+    fn apply(&self, record: &mut MemoRecord) {
+        fn optional(text: &str) -> Option<String> {
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_string())
+        }
+        if let Some(memo) = &self.memo {
+            record.memo = memo.clone();
+        }
+        if let Some(title) = &self.title {
+            record.title = optional(title);
+        }
+        if let Some(folder) = &self.folder {
+            record.folder = normalize_folder(folder);
+        }
+        if let Some(description) = &self.description {
+            record.description = optional(description);
+        }
+    }
+}
+
+// GET /memo/folders 결과 한 줄, count는 하위 폴더까지 포함한 메모 수
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct MemoFolder {
+    pub path: String,
+    pub count: usize,
+}
+
+// 메모를 만든/수정한 시각(ms)과 순서, 휴지통에도 함께 보관
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+struct MemoMeta {
+    created_at: i64,
+    updated_at: i64,
+    position: i64,
+}
+
+impl MemoMeta {
+    fn from_value((created_at, updated_at, position): (i64, i64, i64)) -> Self {
+        MemoMeta { created_at, updated_at, position }
+    }
+
+    fn value(&self) -> (i64, i64, i64) {
+        (self.created_at, self.updated_at, self.position)
+    }
 }
 
 pub struct ClipboardData {
     db: Database,
+    path: String,
     max_items: usize,
+    tokenizer: Box<dyn Tokenizer>,
+    dedup_mode: DedupMode,
+    size_limits: SizeLimits,
+    compression_threshold: u64,
+    max_memo_revisions: usize,
+    encrypted: bool,        // DB에 암호화 키 확인 값이 있음
+    cipher: Option<Cipher>, // 잠금 해제된 키, 암호화된 DB에서 None이면 잠긴 상태
+    #[cfg(test)]
+    cleanup_scanned: std::sync::atomic::AtomicUsize, // 정리할 때 읽은 시간 인덱스 항목 수 (전체 스캔이 없는지 테스트에서 확인)
 }
 
 impl ClipboardData {
     pub fn new(path: String, max_items: usize) -> Self {
+        Self::with_tokenizer(path, max_items, Box::new(HangulTokenizer))
+    }
+
+    // 검색 색인에 쓸 토크나이저를 지정 (바뀌면 열 때 색인을 다시 만든다)
+    pub fn with_tokenizer(path: String, max_items: usize, tokenizer: Box<dyn Tokenizer>) -> Self {
         let dir = std::path::Path::new(&path).parent();
         if let Some(dir) = dir {
             if !dir.exists() {
@@ -45,357 +329,1846 @@ impl ClipboardData {
         }
         let db = Database::create(&path).expect(&format!("Failed to create database at {}", &path));
         info!("Clipboard database using clip.db at {} with max items: {}", &path, max_items);
+        // 이전 버전의 레이아웃이면 백업 후 현재 스키마로 변환
+        let db = migration::run(&path, db);
         // Initialize the tables (클립보드와 메모 테이블 모두 초기화)
         let write_txn = db.begin_write().expect("Failed to begin write transaction");
         {
             let _ = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
             let _ = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+            let _ = write_txn.open_table(IMAGE_TABLE).expect("Failed to open image table");
+            let _ = write_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+            let _ = write_txn.open_table(HASH_INDEX_TABLE).expect("Failed to open hash index table");
+            let _ = write_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+            let _ = write_txn.open_table(MEMO_LINK_TABLE).expect("Failed to open memo link table");
+            let _ = write_txn.open_table(EXPIRY_INDEX_TABLE).expect("Failed to open expiry index table");
+            let _ = write_txn.open_table(MEMO_META_TABLE).expect("Failed to open memo meta table");
+            let _ = write_txn.open_table(MEMO_ORDER_TABLE).expect("Failed to open memo order table");
+        }
+        labels::open_tables(&write_txn);
+        crypto::open_tables(&write_txn);
+        trash::open_tables(&write_txn);
+        revision::open_tables(&write_txn);
+        // 암호화된 DB는 잠금을 해제하기 전에 내용을 읽을 수 없고 색인도 저장하지 않음
+        let encrypted = crypto::is_encrypted(&write_txn);
+        if !encrypted {
+            search::ensure_index(&write_txn, tokenizer.as_ref());
         }
         write_txn.commit().expect("Failed to commit transaction");
+        if encrypted {
+            info!("Clipboard database is encrypted and locked");
+        }
         
-        ClipboardData { db, max_items }
+        ClipboardData {
+            db,
+            path,
+            max_items,
+            tokenizer,
+            dedup_mode: DedupMode::default(),
+            size_limits: SizeLimits::default(),
+            compression_threshold: 0,
+            max_memo_revisions: 0,
+            encrypted,
+            cipher: None,
+            #[cfg(test)]
+            cleanup_scanned: std::sync::atomic::AtomicUsize::new(0),
+        }
     }
 
-    pub fn write(&self, text: &str) {
-        let now = chrono::Local::now();
-        let date_key = now.format("%Y-%m-%d").to_string();
-        let sequence = self.get_next_sequence(&date_key);
-        let full_key = format!("clipboard-{}-{}", date_key, sequence);
-        
-        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
-        {
-            let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
-            table.insert(full_key.as_str(), text)
-                .expect("Failed to insert clipboard data");
+    pub fn set_dedup_mode(&mut self, dedup_mode: DedupMode) {
+        self.dedup_mode = dedup_mode;
+    }
+
+    pub fn set_size_limits(&mut self, size_limits: SizeLimits) {
+        self.size_limits = size_limits;
+    }
+
+    // 이 크기(바이트)보다 큰 항목은 압축해 저장, 0이면 압축하지 않음
+    pub fn set_compression_threshold(&mut self, compression_threshold: u64) {
+        self.compression_threshold = compression_threshold;
+    }
+
+    // 메모마다 보관할 이전 버전 수, 0이면 보관하지 않음
+    pub fn set_max_memo_revisions(&mut self, max_memo_revisions: usize) {
+        self.max_memo_revisions = max_memo_revisions;
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+
+    // 잠긴 동안에는 읽기 결과가 비어 있고 새 항목을 저장하지 않음
+    pub fn is_locked(&self) -> bool {
+        self.encrypted && self.cipher.is_none()
+    }
+
+    // 암호화된 DB의 잠금 해제, 키가 틀리면 잠긴 상태 유지
+    pub fn unlock(&mut self, key: &EncryptionKey) -> Result<(), EncryptionError> {
+        let derived = self.key_check()?.derive(key)?;
+        self.unlock_with(derived)
+    }
+
+    // 키 유도에 필요한 값, 유도한 키는 unlock_with/verify_derived_key로 넘김
+    pub fn key_check(&self) -> Result<KeyCheck, EncryptionError> {
+        if !self.encrypted {
+            return Err(EncryptionError::NotEncrypted);
         }
-        write_txn.commit().expect("Failed to commit transaction");
-        
-        // 최대 개수 제한 확인 및 정리
-        self.cleanup_old_items();
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        crypto::key_check(&read_txn).ok_or(EncryptionError::NotEncrypted)
     }
-    
+
+    // 미리 유도한 키로 잠금 해제, 유도한 뒤에 다시 암호화되었으면 WrongKey
+    pub fn unlock_with(&mut self, key: DerivedKey) -> Result<(), EncryptionError> {
+        self.verify_derived_key(&key)?;
+        self.cipher = Some(key.cipher);
+        info!("Clipboard database unlocked");
+        Ok(())
+    }
+
+    // 키가 지금 DB의 키와 맞는지 확인 (잠금 상태는 바꾸지 않음), 평문 DB면 항상 Ok
     #[allow(dead_code)]
-    pub fn read(&self, date_key: &str, sequence: u64) -> Option<String> {
-        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
-        
-        let full_key = format!("clipboard-{}-{}", date_key, sequence);
-        if let Some(value) = table.get(full_key.as_str()).expect("Failed to get clipboard data") {
-            Some(value.value().to_string())
-        } else {
-            None
+    pub fn verify_key(&self, key: &EncryptionKey) -> Result<(), EncryptionError> {
+        if !self.encrypted {
+            return Ok(());
         }
+        self.verify_derived_key(&self.key_check()?.derive(key)?)
     }
-    
-    #[allow(dead_code)]
-    pub fn read_by_date(&self, date_key: &str) -> Vec<(u64, String)> {
-        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
-        
-        let mut results = Vec::new();
-        let date_prefix = format!("clipboard-{}-", date_key);
-        
-        for item in table.iter().expect("Failed to iterate table") {
-            if let Ok((key, value)) = item {
-                let key_str = key.value();
-                if key_str.starts_with(&date_prefix) {
-                    // 키에서 시퀀스 번호 추출
-                    if let Some(sequence_str) = key_str.strip_prefix(&date_prefix) {
-                        if let Ok(sequence) = sequence_str.parse::<u64>() {
-                            results.push((sequence, value.value().to_string()));
-                        }
-                    }
+
+    pub fn verify_derived_key(&self, key: &DerivedKey) -> Result<(), EncryptionError> {
+        if self.key_check()?.salt() != key.salt.as_slice() {
+            return Err(EncryptionError::WrongKey);
+        }
+        Ok(())
+    }
+
+    /**
+     * 저장된 값(클립보드 레코드, 이미지, 메모, 외부 저장 파일)을 모두 새 키로 다시 암호화
+     * 평문 DB를 처음 암호화하거나 passphrase/키 파일을 바꿀 때 쓰며, key가 None이면 복호화해 평문으로 되돌린다.
+     * 매번 새 salt를 쓰므로 같은 passphrase로 다시 암호화해도 키가 바뀐다.
+     * 잠긴 DB는 먼저 unlock해야 하며, 다시 쓴 값의 개수를 반환한다.
+     */
+    pub fn rekey(&mut self, key: Option<&EncryptionKey>) -> Result<usize, EncryptionError> {
+        if self.is_locked() {
+            return Err(EncryptionError::Locked);
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let cipher = match key {
+            Some(key) => Some(crypto::enable(&write_txn, key)?),
+            None => {
+                crypto::disable(&write_txn);
+                None
+            }
+        };
+
+        // 외부 저장 파일은 임시 파일에 새 키로 써 두었다가 커밋한 뒤에 바꿔 넣음
+        // 중간에 실패하면 트랜잭션을 버리고 임시 파일도 지우므로 DB와 파일 모두 이전 키로 남는다.
+        let dir = self.blob_dir();
+        let mut staged = Vec::new();
+        let count = match self.rekey_values(&write_txn, self.cipher.as_ref(), cipher.as_ref(), &mut staged) {
+            Ok(count) => count,
+            Err(e) => {
+                for id in &staged {
+                    storage::discard_staged(&dir, *id);
                 }
+                return Err(e);
+            }
+        };
+        write_txn.commit().expect("Failed to commit transaction");
+        for id in &staged {
+            if let Err(e) = storage::commit_staged(&dir, *id) {
+                error!("Failed to replace external content of clipboard item {}: {}", id, e);
             }
         }
-        
-        // 시퀀스 번호로 정렬
-        results.sort_by(|a, b| a.0.cmp(&b.0));
-        results
+
+        self.encrypted = cipher.is_some();
+        self.cipher = cipher;
+        info!("Re-encrypted {} stored values ({})", count, if self.encrypted { "encrypted" } else { "plaintext" });
+        Ok(count)
     }
 
-    #[allow(dead_code)]
-    pub fn read_last(&self, count: usize) -> Vec<(String, u64, String)> {
-        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
-        
-        let mut all_results = Vec::new();
-        
-        // 모든 데이터를 수집하고 키에서 날짜와 시퀀스를 파싱
-        for item in table.iter().expect("Failed to iterate table") {
-            if let Ok((key, value)) = item {
-                let key_str = key.value();
-                // 키 형식: "clipboard-YYYY-MM-DD-sequence"에서 날짜와 시퀀스 분리
-                if let Some(clipboard_prefix) = key_str.strip_prefix("clipboard-") {
-                    if let Some(last_dash) = clipboard_prefix.rfind('-') {
-                        let date_part = &clipboard_prefix[..last_dash];
-                        let sequence_part = &clipboard_prefix[last_dash + 1..];
-                        
-                        if let Ok(sequence) = sequence_part.parse::<u64>() {
-                            all_results.push((date_part.to_string(), sequence, value.value().to_string()));
-                        }
-                    }
+    // 읽지 못하는 값이 하나라도 있으면 값을 잃지 않도록 바로 실패
+    fn rekey_values(
+        &self,
+        write_txn: &WriteTransaction,
+        old: Option<&Cipher>,
+        new: Option<&Cipher>,
+        staged: &mut Vec<u64>,
+    ) -> Result<usize, EncryptionError> {
+        let mut count = 0;
+        {
+            let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+            let records: Vec<(u64, ClipboardRecord)> = table.iter()
+                .expect("Failed to iterate clipboard table")
+                .map(|item| {
+                    let (key, value) = item.expect("Failed to read clipboard data");
+                    let record = ClipboardRecord::decode(value.value(), old)
+                        .ok_or_else(|| EncryptionError::Undecodable(format!("clipboard item {}", key.value())))?;
+                    Ok((key.value(), record))
+                })
+                .collect::<Result<_, EncryptionError>>()?;
+            for (id, record) in &records {
+                table.insert(*id, record.encode(new).as_slice())
+                    .expect("Failed to update clipboard data");
+                if record.external && self.stage_external(*id, old, new)? {
+                    staged.push(*id);
+                }
+            }
+            count += records.len();
+
+            // 해시 색인의 키는 cipher에 따라 달라지므로 가리키던 항목의 해시로 다시 만듦
+            let hashes: std::collections::HashMap<u64, &str> = records.iter()
+                .map(|(id, record)| (*id, record.hash.as_str()))
+                .collect();
+            let mut hash_index = write_txn.open_table(HASH_INDEX_TABLE).expect("Failed to open hash index table");
+            let entries: Vec<(String, u64)> = hash_index.iter()
+                .expect("Failed to iterate hash index")
+                .filter_map(|item| item.ok())
+                .map(|(key, value)| (key.value().to_string(), value.value()))
+                .collect();
+            for (key, id) in &entries {
+                hash_index.remove(key.as_str()).expect("Failed to update hash index");
+                if let Some(hash) = hashes.get(id) {
+                    hash_index.insert(crypto::index_hash(new, hash).as_str(), *id)
+                        .expect("Failed to update hash index");
                 }
             }
+
+            let mut image_table = write_txn.open_table(IMAGE_TABLE).expect("Failed to open image table");
+            let images: Vec<(u64, Vec<u8>)> = image_table.iter()
+                .expect("Failed to iterate image table")
+                .map(|item| {
+                    let (key, value) = item.expect("Failed to read image data");
+                    let png = crypto::open(old, value.value())
+                        .ok_or_else(|| EncryptionError::Undecodable(format!("image {}", key.value())))?;
+                    Ok((key.value(), png.into_owned()))
+                })
+                .collect::<Result<_, EncryptionError>>()?;
+            for (id, png) in images {
+                image_table.insert(id, crypto::seal(new, png).as_slice())
+                    .expect("Failed to update image data");
+                count += 1;
+            }
+
+            let mut memo_table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+            let memos: Vec<(u64, MemoRecord)> = memo_table.iter()
+                .expect("Failed to iterate memo table")
+                .map(|item| {
+                    let (key, value) = item.expect("Failed to read memo");
+                    let memo = MemoRecord::decode(value.value(), old)
+                        .ok_or_else(|| EncryptionError::Undecodable(format!("memo {}", key.value())))?;
+                    Ok((key.value(), memo))
+                })
+                .collect::<Result<_, EncryptionError>>()?;
+            for (id, memo) in &memos {
+                memo_table.insert(*id, memo.encode(new).as_slice())
+                    .expect("Failed to update memo");
+            }
+            count += memos.len();
         }
-        
-        // 날짜를 기준으로 내림차순, 같은 날짜면 시퀀스를 기준으로 내림차순 정렬
-        all_results.sort_by(|a, b| {
-            match b.0.cmp(&a.0) { // 날짜 내림차순
-                std::cmp::Ordering::Equal => b.1.cmp(&a.1), // 시퀀스 내림차순
-                other => other,
+        count += revision::rekey(write_txn, old, new)?;
+        // 휴지통의 외부 저장 항목은 파일도 함께
+        let trashed = trash::rekey(write_txn, old, new)?;
+        for (id, entry) in &trashed {
+            if let TrashedItem::Clipboard { record } = &entry.item && record.external && self.stage_external(*id, old, new)? {
+                staged.push(*id);
             }
-        });
-        
-        // 요청된 개수만큼 반환
-        all_results.into_iter().take(count).collect()
+        }
+        count += trashed.len();
+        match new {
+            Some(_) => search::clear_index(write_txn),
+            None => search::rebuild_index(write_txn, self.tokenizer.as_ref()),
+        }
+        Ok(count)
     }
 
+    // 외부 저장 파일을 새 키로 임시 파일에 씀, 파일이 없으면 false
+    fn stage_external(&self, id: u64, old: Option<&Cipher>, new: Option<&Cipher>) -> Result<bool, EncryptionError> {
+        let dir = self.blob_dir();
+        if !storage::has_external(&dir, id) {
+            warn!("External content of clipboard item {} is missing", id);
+            return Ok(false);
+        }
+        let record = storage::read_external(&dir, id, old)
+            .ok_or_else(|| EncryptionError::Undecodable(format!("external content of clipboard item {}", id)))?;
+        let png = match storage::has_external_image(&dir, id) {
+            true => Some(storage::read_external_image(&dir, id, old)
+                .ok_or_else(|| EncryptionError::Undecodable(format!("external image of clipboard item {}", id)))?),
+            false => None,
+        };
+        storage::stage_external(&dir, id, &record, png.as_deref(), new).map_err(EncryptionError::External)?;
+        Ok(true)
+    }
 
-    fn get_next_sequence(&self, date_key: &str) -> u64 {
-        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
-        
-        let mut max_sequence = 0u64;
-        let date_prefix = format!("clipboard-{}-", date_key);
+    fn cipher(&self) -> Option<&Cipher> {
+        self.cipher.as_ref()
+    }
+
+    pub fn write(&self, text: &str) {
+        let record = ClipboardRecord::text(now_millis(), text);
+        self.insert_record(record, None);
+    }
+
+    // 비밀 정보가 감지된 텍스트 저장: sensitive 플래그를 붙이고 expires_at(ms)이 있으면 그 시각에 삭제
+    // 내용이 가려지도록 검색 대상에서 제외한다.
+    pub fn write_secret(&self, text: &str, expires_at: Option<i64>) {
+        let mut record = ClipboardRecord::text(now_millis(), text);
+        record.sensitive = true;
+        record.expires_at = expires_at;
+        self.insert_record(record, None);
+    }
+
+    // 서식 있는 텍스트 저장: 본문에는 plain text, HTML은 레코드에 함께 저장
+    pub fn write_html(&self, text: &str, html: &str) {
+        let record = ClipboardRecord::html(now_millis(), text, html);
+        self.insert_record(record, None);
+    }
+
+    // 복사된 파일 목록 저장: 본문에는 줄바꿈으로 연결한 경로
+    pub fn write_files(&self, paths: &[String]) {
+        let record = ClipboardRecord::files(now_millis(), paths);
+        self.insert_record(record, None);
+    }
+
+    // 이미지 클립보드 저장: 본문에는 설명 문자열, PNG 바이트는 IMAGE_TABLE에 같은 id로 저장
+    pub fn write_image(&self, png: &[u8], width: usize, height: usize) {
+        let description = format!("[image {}x{}]", width, height);
+        let record = ClipboardRecord::image(now_millis(), &description, png);
+        self.insert_record(record, Some(png));
+    }
+
+    fn insert_record(&self, mut record: ClipboardRecord, mut png: Option<&[u8]>) {
+        if self.is_locked() {
+            warn!("Clipboard database is locked; {:?} clipboard content not saved", record.kind);
+            return;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        if let Some(id) = self.find_duplicate(&write_txn, &record.hash) {
+            self.move_to_top(&write_txn, id, &record);
+            write_txn.commit().expect("Failed to commit transaction");
+            info!("Clipboard content already stored as item {}; moved to top", id);
+            return;
+        }
+
+        let id = allocate_id(&write_txn);
+        if !self.prepare_record(id, &mut record, &mut png) {
+            return;
+        }
+        self.insert_item(&write_txn, id, &record, png);
         
-        for item in table.iter().expect("Failed to iterate table") {
-            let (key, _) = item.expect("Failed to read item");
-            let key_str = key.value();
-            
-            // 해당 날짜의 키인지 확인
-            if key_str.starts_with(&date_prefix) {
-                // 키에서 시퀀스 번호 추출 (예: "clipboard-2025-08-10-3"에서 "3" 추출)
-                if let Some(sequence_str) = key_str.strip_prefix(&date_prefix) {
-                    if let Ok(sequence) = sequence_str.parse::<u64>() {
-                        if sequence > max_sequence {
-                            max_sequence = sequence;
-                        }
+        // 최대 개수와 전체 크기 제한 확인 및 정리 (같은 트랜잭션에서 처리)
+        let removed = self.cleanup_old_items(&write_txn, id);
+        write_txn.commit().expect("Failed to commit transaction");
+        if record.external {
+            self.commit_external(id);
+        }
+
+        if removed > 0 {
+            info!("Cleaned up {} old clipboard items (max_items: {}, max_history_bytes: {})",
+                removed, self.max_items, self.size_limits.max_history_bytes);
+        }
+    }
+    
+    // 크기 제한(OversizePolicy)과 압축 여부를 적용, 저장하지 않아야 하면 false
+    // 외부 저장 파일은 임시 파일로만 써 두므로 트랜잭션을 커밋한 뒤 commit_external을 호출해야 함
+    fn prepare_record(&self, id: u64, record: &mut ClipboardRecord, png: &mut Option<&[u8]>) -> bool {
+        if self.size_limits.exceeds_item(record.size) {
+            let max_bytes = self.size_limits.max_item_bytes;
+            match self.size_limits.oversize_policy {
+                OversizePolicy::Truncate if record.kind == ClipboardKind::Text => {
+                    let size = record.size;
+                    storage::truncate(record, max_bytes);
+                    record.size = record.content.len() as u64;
+                    info!("Clipboard content of {} bytes truncated to {} bytes", size, record.size);
+                }
+                OversizePolicy::External => {
+                    if let Err(e) = storage::stage_external(&self.blob_dir(), id, record, *png, self.cipher()) {
+                        error!("Failed to store clipboard content of {} bytes externally: {}", record.size, e);
+                        storage::discard_staged(&self.blob_dir(), id);
+                        return false;
                     }
+                    // size는 전체 크기 그대로 두어 max_history_bytes에 포함
+                    storage::truncate(record, max_bytes);
+                    record.external = true;
+                    *png = None;
+                    info!("Clipboard content of {} bytes stored externally as item {}", record.size, id);
+                }
+                _ => {
+                    info!("Skipped {:?} clipboard content of {} bytes (max_item_bytes: {})", record.kind, record.size, max_bytes);
+                    return false;
                 }
             }
         }
-        max_sequence + 1
+        // 이미지는 PNG 자체가 압축되어 있고 레코드에는 설명만 있으므로 제외
+        record.compressed = self.compression_threshold > 0
+            && record.kind != ClipboardKind::Image
+            && record.size > self.compression_threshold;
+        true
     }
 
-    pub fn get_clipboard_items(&self, count: Option<usize>) -> Vec<ClipboardItem> {
+    // prepare_record가 임시 파일로 써 둔 외부 저장 파일을 바꿔 넣음
+    fn commit_external(&self, id: u64) {
+        if let Err(e) = storage::commit_staged(&self.blob_dir(), id) {
+            error!("Failed to store external content of clipboard item {}: {}", id, e);
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn read(&self, id: u64) -> Option<String> {
+        self.read_record(id).map(|record| record.content)
+    }
+
+    // 외부 저장된 항목은 파일에서 전체 본문을 읽어 채움
+    pub fn read_record(&self, id: u64) -> Option<ClipboardRecord> {
         let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let clipboard_table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
-        
-        let mut all_results = Vec::new();
+        let table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
         
-        // 모든 클립보드 데이터를 수집
-        for item in clipboard_table.iter().expect("Failed to iterate clipboard table") {
-            if let Ok((key, value)) = item {
-                let key_str = key.value();
-                // 키 형식: "clipboard-YYYY-MM-DD-sequence"에서 날짜와 시퀀스 분리
-                if let Some(clipboard_prefix) = key_str.strip_prefix("clipboard-") {
-                    if let Some(last_dash) = clipboard_prefix.rfind('-') {
-                        let date_part = &clipboard_prefix[..last_dash];
-                        let sequence_part = &clipboard_prefix[last_dash + 1..];
-                        
-                        if let Ok(sequence) = sequence_part.parse::<u64>() {
-                            all_results.push(ClipboardItem {
-                                date: date_part.to_string(),
-                                sequence,
-                                content: value.value().to_string(),
-                            });
-                        }
-                    }
+        let value = table.get(id).expect("Failed to get clipboard data")?;
+        let mut record = ClipboardRecord::decode(value.value(), self.cipher())?;
+        if record.external {
+            match storage::read_external(&self.blob_dir(), id, self.cipher()) {
+                Some(full) => {
+                    record.content = full.content;
+                    record.html = full.html;
+                    record.files = full.files;
                 }
+                None => warn!("External content of clipboard item {} is missing", id),
             }
         }
-        
-        // 날짜를 기준으로 내림차순, 같은 날짜면 시퀀스를 기준으로 내림차순 정렬
-        all_results.sort_by(|a, b| {
-            match b.date.cmp(&a.date) {
-                std::cmp::Ordering::Equal => b.sequence.cmp(&a.sequence),
-                other => other,
-            }
-        });
-        
-        // 요청된 개수만큼 반환
-        if let Some(count) = count {
-            all_results.into_iter().take(count).collect()
-        } else {
-            all_results
+        Some(record)
+    }
+    
+    pub fn read_image(&self, id: u64) -> Option<Vec<u8>> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        let table = read_txn.open_table(IMAGE_TABLE).expect("Failed to open image table");
+
+        match table.get(id).expect("Failed to get image data") {
+            Some(value) => crypto::open(self.cipher(), value.value()).map(|png| png.into_owned()),
+            None => storage::read_external_image(&self.blob_dir(), id, self.cipher()),
         }
     }
 
-    // 오래된 항목들을 정리하여 최대 개수를 유지
-    fn cleanup_old_items(&self) {
+    pub fn get_clipboard_item(&self, id: u64) -> Option<ClipboardItem> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        let table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+
+        let value = table.get(id).expect("Failed to get clipboard data")?;
+        let record = ClipboardRecord::decode(value.value(), self.cipher())?;
+        Some(ClipboardItem::from_record(id, record).with_annotations(&read_txn, self.cipher()))
+    }
+
+    // 외부 저장된 항목도 전체 본문으로 조회 (붙여넣기용, 목록과 검색에는 잘린 본문이 쓰임)
+    pub fn get_full_clipboard_item(&self, id: u64) -> Option<ClipboardItem> {
+        let record = self.read_record(id)?;
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        Some(ClipboardItem::from_record(id, record).with_annotations(&read_txn, self.cipher()))
+    }
+
+    // 항목 수와 크기 사용량
+    pub fn storage_stats(&self) -> StorageStats {
         let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
         let clipboard_table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
-        
-        let mut all_items = Vec::new();
-        
-        // 모든 클립보드 데이터를 수집
-        for item in clipboard_table.iter().expect("Failed to iterate clipboard table") {
-            if let Ok((key, _)) = item {
-                let key_str = key.value();
-                // 키 형식: "clipboard-YYYY-MM-DD-sequence"에서 날짜와 시퀀스 분리
-                if let Some(clipboard_prefix) = key_str.strip_prefix("clipboard-") {
-                    if let Some(last_dash) = clipboard_prefix.rfind('-') {
-                        let date_part = &clipboard_prefix[..last_dash];
-                        let sequence_part = &clipboard_prefix[last_dash + 1..];
-                        
-                        if let Ok(sequence) = sequence_part.parse::<u64>() {
-                            all_items.push((date_part.to_string(), sequence, key_str.to_string()));
-                        }
-                    }
-                }
+        let pinned_table = read_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+        let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+        let metadata = read_txn.open_table(migration::METADATA_TABLE).expect("Failed to open metadata table");
+
+        let (mut external_count, mut external_bytes) = (0, 0);
+        let (mut compressed_count, mut compressed_bytes, mut uncompressed_bytes) = (0, 0, 0);
+        for (_, value) in clipboard_table.iter()
+            .expect("Failed to iterate clipboard table")
+            .filter_map(|item| item.ok())
+        {
+            let Some(record) = ClipboardRecord::decode(value.value(), self.cipher()) else {
+                continue;
+            };
+            if record.external {
+                external_count += 1;
+                external_bytes += record.size;
             }
-        }
-        drop(read_txn); // 읽기 트랜잭션 종료
-        
-        // 항목 수가 최대치를 초과하는 경우에만 정리
-        if all_items.len() > self.max_items {
-            // 날짜를 기준으로 내림차순, 같은 날짜면 시퀀스를 기준으로 내림차순 정렬
-            all_items.sort_by(|a, b| {
-                match b.0.cmp(&a.0) {
-                    std::cmp::Ordering::Equal => b.1.cmp(&a.1),
-                    other => other,
-                }
-            });
-            
-            // 최대치를 초과하는 오래된 항목들 삭제
-            let items_to_delete = &all_items[self.max_items..];
-            
-            let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
-            {
-                let mut clipboard_table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
-                
-                for (_, _, key) in items_to_delete {
-                    clipboard_table.remove(key.as_str()).ok(); // 에러 무시
-                }
+            if record.compressed {
+                compressed_count += 1;
+                compressed_bytes += value.value().len() as u64;
+                uncompressed_bytes += record.to_json().len() as u64;
             }
-            write_txn.commit().expect("Failed to commit transaction");
-            
-            info!("Cleaned up {} old clipboard items. Current count: {}", 
-                     items_to_delete.len(), self.max_items);
         }
-    }
-
-    // 메모 관련 메서드들
-    pub fn add_memo(&self, memo: &str) -> String {
-        let sequence = self.get_next_memo_sequence();
-        let full_key = format!("memo-{}", sequence);
-        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
-        {
-            let mut table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
-            table.insert(full_key.as_str(), memo).expect("Failed to insert memo");
+        StorageStats {
+            item_count: clipboard_table.len().expect("Failed to count clipboard items"),
+            pinned_count: pinned_table.len().expect("Failed to count pinned items"),
+            memo_count: memo_table.len().expect("Failed to count memos"),
+            total_bytes: metadata.get(migration::TOTAL_BYTES_KEY)
+                .expect("Failed to read total bytes")
+                .map(|value| value.value())
+                .unwrap_or(0),
+            external_count,
+            external_bytes,
+            database_bytes: std::fs::metadata(&self.path).map(|metadata| metadata.len()).unwrap_or(0),
+            compressed_count,
+            compressed_bytes,
+            uncompressed_bytes,
+            compression_ratio: if compressed_bytes > 0 { uncompressed_bytes as f64 / compressed_bytes as f64 } else { 1.0 },
+            max_items: self.max_items as u64,
+            max_item_bytes: self.size_limits.max_item_bytes,
+            max_history_bytes: self.size_limits.max_history_bytes,
         }
-        write_txn.commit().expect("Failed to commit transaction");
-        full_key
     }
 
-    #[allow(dead_code)]
-    pub fn get_memo(&self, sequence: u64) -> Option<String> {
-        let full_key = format!("memo-{}", sequence);
-        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
-        
-        if let Some(value) = table.get(full_key.as_str()).expect("Failed to get memo") {
-            Some(value.value().to_string())
-        } else {
-            None
-        }
+    fn blob_dir(&self) -> std::path::PathBuf {
+        storage::blob_dir(&self.path)
     }
 
-    pub fn update_memo(&self, sequence: u64, memo: &str) {
-        let full_key = format!("memo-{}", sequence);
+    // 항목을 다시 붙여넣을 때 사용 횟수 증가
+    pub fn record_use(&self, id: u64) {
         let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
         {
-            let mut table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
-            table.insert(full_key.as_str(), memo).expect("Failed to insert memo");
+            let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+            let record = table.get(id).expect("Failed to get clipboard data")
+                .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()));
+            if let Some(mut record) = record {
+                record.use_count += 1;
+                table.insert(id, record.encode(self.cipher()).as_slice())
+                    .expect("Failed to update clipboard data");
+            }
         }
         write_txn.commit().expect("Failed to commit transaction");
     }
-
-    pub fn delete_memo(&self, sequence: u64) {
-        let full_key = format!("memo-{}", sequence);
+    
+    // 항목 고정/해제, 항목이 없으면 false
+    // 고정을 해제해 max_items를 넘게 되면 다음 복사 때 정리된다.
+    pub fn set_pinned(&self, id: u64, pinned: bool) -> bool {
         let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
         {
-            let mut table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
-            table.remove(full_key.as_str()).expect("Failed to remove memo");
+            let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+            let record = table.get(id).expect("Failed to get clipboard data")
+                .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()));
+            let Some(mut record) = record else {
+                return false;
+            };
+            record.pinned = pinned;
+            table.insert(id, record.encode(self.cipher()).as_slice())
+                .expect("Failed to update clipboard data");
+
+            let mut pinned_table = write_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+            if pinned {
+                pinned_table.insert(id, ()).expect("Failed to update pinned table");
+            } else {
+                pinned_table.remove(id).expect("Failed to update pinned table");
+            }
         }
         write_txn.commit().expect("Failed to commit transaction");
+        true
     }
 
+    // 해당 날짜(로컬 시간 기준)에 복사된 항목들을 시간 순으로 (id, 본문) 반환
     #[allow(dead_code)]
-    pub fn get_memo_items(&self, count: Option<usize>) -> Vec<MemoItem> {
+    pub fn read_by_date(&self, date_key: &str) -> Vec<(u64, String)> {
+        let Some((start, end)) = day_range(date_key) else {
+            return Vec::new();
+        };
         let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
-        
-        let mut all_results = Vec::new();
-        
-        // 모든 메모 데이터를 수집
-        for item in memo_table.iter().expect("Failed to iterate memo table") {
-            if let Ok((key, value)) = item {
-                let key_str = key.value();
-                // memo-sequence 형식에서 sequence 추출
-                if let Some(sequence_str) = key_str.strip_prefix("memo-") {
-                    if let Ok(sequence) = sequence_str.parse::<u64>() {
-                        all_results.push(MemoItem {
-                            date: String::new(), // memo는 더 이상 날짜를 사용하지 않음
-                            sequence,
-                            memo: value.value().to_string(),
-                        });
-                    }
-                }
-            }
-        }
-        
-        // 시퀀스를 기준으로 내림차순 정렬
-        all_results.sort_by(|a, b| b.sequence.cmp(&a.sequence));
-        
-        // 요청된 개수만큼 반환
-        if let Some(count) = count {
-            all_results.into_iter().take(count).collect()
-        } else {
-            all_results
-        }
+        let index = read_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+        let table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+
+        index.range((start, 0)..(end, 0))
+            .expect("Failed to iterate time index")
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, _)| {
+                let (_, id) = key.value();
+                let value = table.get(id).ok()??;
+                let record = ClipboardRecord::decode(value.value(), self.cipher())?;
+                Some((id, record.content))
+            })
+            .collect()
     }
 
     #[allow(dead_code)]
-    pub fn get_memo_item(&self, sequence: u64) -> Option<MemoItem> {
-        let full_key = format!("memo-{}", sequence);
+    pub fn read_last(&self, count: usize) -> Vec<(String, u64, String)> {
+        self.get_clipboard_items(Some(count))
+            .into_iter()
+            .map(|item| (item.date, item.id, item.content))
+            .collect()
+    }
+
+    pub fn get_clipboard_items(&self, count: Option<usize>) -> Vec<ClipboardItem> {
+        self.query_clipboard_items(&ClipboardQuery { count, ..Default::default() }).items
+    }
+
+    // 커서/기간/종류 조건으로 한 페이지 조회 (결과는 항상 최신순)
+    pub fn query_clipboard_items(&self, query: &ClipboardQuery) -> ClipboardPage {
+        let Some(bounds) = query.bounds() else {
+            return ClipboardPage { items: Vec::new(), next_cursor: None };
+        };
+
         let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+        let index = read_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+        let clipboard_table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
         
-        if let Some(value) = table.get(full_key.as_str()).expect("Failed to get memo") {
-            Some(MemoItem {
-                date: String::new(), // memo는 더 이상 날짜를 사용하지 않음
-                sequence,
-                memo: value.value().to_string(),
-            })
+        // 인덱스가 (복사 시각, id) 순으로 정렬되어 있으므로 역순으로 읽으면 최신순
+        let range = index.range::<(i64, u64)>(bounds).expect("Failed to iterate time index");
+        let keys: Box<dyn Iterator<Item = _>> = if query.ascending() {
+            Box::new(range)
         } else {
-            None
+            Box::new(range.rev())
+        };
+
+        // 다음 페이지가 있는지 알기 위해 하나 더 읽음
+        let limit = query.count.map_or(usize::MAX, |count| count.saturating_add(1));
+        let mut items: Vec<ClipboardItem> = keys
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, _)| {
+                let (_, id) = key.value();
+                let value = clipboard_table.get(id).ok()??;
+                let record = ClipboardRecord::decode(value.value(), self.cipher())?;
+                Some(ClipboardItem::from_record(id, record).with_annotations(&read_txn, self.cipher()))
+            })
+            .filter(|item| query.matches(item))
+            .skip(query.offset)
+            .take(limit)
+            .collect();
+
+        let next_cursor = match query.count {
+            Some(count) if items.len() > count => {
+                items.truncate(count);
+                items.last().map(|item| Cursor::of(item).to_string())
+            }
+            _ => None,
+        };
+        if query.ascending() {
+            items.reverse();
+        }
+        if query.pinned_first_page() {
+            let mut pinned = self.pinned_items(&read_txn, query);
+            pinned.append(&mut items);
+            items = pinned;
         }
+        ClipboardPage { items, next_cursor }
     }
 
-    fn get_next_memo_sequence(&self) -> u64 {
-        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
-        let table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
-        
-        let mut max_sequence = 0u64;
-        
-        for item in table.iter().expect("Failed to iterate table") {
-            let (key, _) = item.expect("Failed to read item");
-            let key_str = key.value();
-            
-            // memo-sequence 형식에서 sequence 추출
-            if let Some(sequence_str) = key_str.strip_prefix("memo-") {
-                if let Ok(sequence) = sequence_str.parse::<u64>() {
-                    if sequence > max_sequence {
-                        max_sequence = sequence;
+    // 조건에 맞는 고정 항목 전체 (최신순)
+    fn pinned_items(&self, read_txn: &ReadTransaction, query: &ClipboardQuery) -> Vec<ClipboardItem> {
+        let pinned_table = read_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+        let clipboard_table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+        let mut items: Vec<ClipboardItem> = pinned_table.iter()
+            .expect("Failed to iterate pinned table")
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, _)| {
+                let id = key.value();
+                let value = clipboard_table.get(id).ok()??;
+                let record = ClipboardRecord::decode(value.value(), self.cipher())?;
+                Some(ClipboardItem::from_record(id, record).with_annotations(read_txn, self.cipher()))
+            })
+            .filter(|item| query.matches_pinned(item))
+            .collect();
+        items.sort_by_key(|item| std::cmp::Reverse(Cursor::of(item)));
+        items
+    }
+
+    // dedup_mode에 따라 같은 내용(해시)으로 저장된 항목의 id
+    fn find_duplicate(&self, write_txn: &WriteTransaction, hash: &str) -> Option<u64> {
+        match self.dedup_mode {
+            DedupMode::Off => None,
+            DedupMode::Consecutive => {
+                let index = write_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+                let (key, _) = index.last().expect("Failed to read time index")?;
+                let (_, id) = key.value();
+                let table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+                let value = table.get(id).expect("Failed to get clipboard data")?;
+                let record = ClipboardRecord::decode(value.value(), self.cipher())?;
+                (record.hash == hash).then_some(id)
+            }
+            DedupMode::Global => {
+                let hash_index = write_txn.open_table(HASH_INDEX_TABLE).expect("Failed to open hash index table");
+                let id = hash_index.get(crypto::index_hash(self.cipher(), hash).as_str())
+                    .expect("Failed to read hash index")?
+                    .value();
+                Some(id)
+            }
+        }
+    }
+
+    // 다시 복사된 항목의 복사 시각을 갱신해 목록 맨 위로 올리고 사용 횟수 증가
+    // 둘 중 하나라도 비밀 정보면 sensitive로 (검색 색인에서 제외), 만료 시각은 더 이른 쪽을 따름
+    fn move_to_top(&self, write_txn: &WriteTransaction, id: u64, copied: &ClipboardRecord) {
+        let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+        let record = table.get(id).expect("Failed to get clipboard data")
+            .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()));
+        let Some(mut record) = record else {
+            return;
+        };
+
+        let mut index = write_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+        index.remove((record.timestamp, id)).expect("Failed to update time index");
+        index.insert((copied.timestamp, id), ()).expect("Failed to update time index");
+        record.timestamp = copied.timestamp;
+        record.use_count += 1;
+        self.merge_flags(write_txn, id, &mut record, copied);
+        table.insert(id, record.encode(self.cipher()).as_slice())
+            .expect("Failed to update clipboard data");
+    }
+
+    // 같은 내용의 다른 복사본(other)의 비밀 정보 플래그를 합침 (레코드는 호출한 쪽에서 저장)
+    fn merge_flags(&self, write_txn: &WriteTransaction, id: u64, record: &mut ClipboardRecord, other: &ClipboardRecord) {
+        if other.sensitive && !record.sensitive {
+            self.unindex_document(write_txn, id, &record.content);
+            record.sensitive = true;
+        }
+        let expires_at = match (record.expires_at, other.expires_at) {
+            (Some(current), Some(other)) => Some(current.min(other)),
+            (current, other) => current.or(other),
+        };
+        if expires_at != record.expires_at {
+            let mut expiry_index = write_txn.open_table(EXPIRY_INDEX_TABLE).expect("Failed to open expiry index table");
+            if let Some(current) = record.expires_at {
+                expiry_index.remove((current, id)).expect("Failed to update expiry index");
+            }
+            if let Some(expires_at) = expires_at {
+                expiry_index.insert((expires_at, id), ()).expect("Failed to update expiry index");
+            }
+            record.expires_at = expires_at;
+        }
+    }
+
+    // 오래된 항목들을 정리하여 최대 개수와 전체 크기(max_history_bytes)를 유지, 삭제한 개수 반환
+    // 고정된 항목은 개수에 포함하지 않고 삭제하지도 않는다. 크기에는 포함된다.
+    // keep은 방금 저장한 항목으로, 혼자 크기 예산을 넘더라도 삭제하지 않는다.
+    fn cleanup_old_items(&self, write_txn: &WriteTransaction, keep: u64) -> usize {
+        let pinned_table = write_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+        let total = {
+            let clipboard_table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+            clipboard_table.len().expect("Failed to count clipboard items") as usize
+        };
+        let unpinned = total.saturating_sub(pinned_table.len().expect("Failed to count pinned items") as usize);
+        let mut excess_items = unpinned.saturating_sub(self.max_items);
+        let mut excess_bytes = match self.size_limits.max_history_bytes {
+            0 => 0,
+            max_bytes => total_bytes(write_txn).saturating_sub(max_bytes),
+        };
+        // 항목 수나 크기가 최대치를 초과하는 경우에만 정리
+        if excess_items == 0 && excess_bytes == 0 {
+            return 0;
+        }
+
+        // 복사 시각이 가장 오래된 항목부터 두 제한을 모두 만족할 때까지 삭제
+        let items_to_delete: Vec<u64> = {
+            let index = write_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+            let clipboard_table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+            let mut ids = Vec::new();
+            for (key, _) in index.iter().expect("Failed to iterate time index").filter_map(|item| item.ok()) {
+                if excess_items == 0 && excess_bytes == 0 {
+                    break;
+                }
+                #[cfg(test)]
+                self.cleanup_scanned.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                let id = key.value().1;
+                if id == keep || matches!(pinned_table.get(id), Ok(Some(_))) {
+                    continue;
+                }
+                let size = clipboard_table.get(id).ok().flatten()
+                    .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()))
+                    .map_or(0, |record| record.size);
+                excess_items = excess_items.saturating_sub(1);
+                excess_bytes = excess_bytes.saturating_sub(size);
+                ids.push(id);
+            }
+            ids
+        };
+        drop(pinned_table);
+        for id in &items_to_delete {
+            self.remove_item(write_txn, *id);
+        }
+        items_to_delete.len()
+    }
+
+    // cutoff(ms)보다 먼저 복사된 항목을 삭제 (고정된 항목 제외), 삭제한 개수 반환
+    // 휴지통을 거치지 않고 바로 삭제
+    pub fn prune_before(&self, cutoff: i64) -> usize {
+        self.remove_range(None, Some(cutoff), false, false)
+    }
+
+    // 클립보드 항목 하나를 휴지통으로 옮김 (붙은 메모는 일반 메모로 남음), 항목이 없으면 false
+    pub fn delete_item(&self, id: u64) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let removed = self.trash_item(&write_txn, id, now_millis());
+        write_txn.commit().expect("Failed to commit transaction");
+        removed
+    }
+
+    // 복사 시각(ms)이 [since, until) 범위인 항목을 휴지통으로 옮김, 옮긴 개수 반환
+    // include_pinned가 false이면 고정된 항목은 남긴다.
+    pub fn delete_range(&self, since: Option<i64>, until: Option<i64>, include_pinned: bool) -> usize {
+        self.remove_range(since, until, include_pinned, true)
+    }
+
+    fn remove_range(&self, since: Option<i64>, until: Option<i64>, include_pinned: bool, to_trash: bool) -> usize {
+        if self.is_locked() {
+            return 0;
+        }
+        let start = Bound::Included((since.unwrap_or(i64::MIN), 0));
+        let end = match until {
+            Some(until) => Bound::Excluded((until, 0)),
+            None => Bound::Unbounded,
+        };
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let items_to_delete: Vec<u64> = {
+            let index = write_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+            let pinned_table = write_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+            index.range::<(i64, u64)>((start, end))
+                .expect("Failed to iterate time index")
+                .filter_map(|item| item.ok())
+                .map(|(key, _)| key.value().1)
+                .filter(|id| include_pinned || !matches!(pinned_table.get(*id), Ok(Some(_))))
+                .collect()
+        };
+        let deleted_at = now_millis();
+        for id in &items_to_delete {
+            if to_trash {
+                self.trash_item(&write_txn, *id, deleted_at);
+            } else {
+                self.remove_item(&write_txn, *id);
+            }
+        }
+        write_txn.commit().expect("Failed to commit transaction");
+        items_to_delete.len()
+    }
+
+    // 만료 시각이 now(ms) 이전인 항목을 삭제 (고정된 항목 제외), 삭제한 개수 반환
+    pub fn prune_expired(&self, now: i64) -> usize {
+        if self.is_locked() {
+            return 0;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let items_to_delete: Vec<u64> = {
+            let index = write_txn.open_table(EXPIRY_INDEX_TABLE).expect("Failed to open expiry index table");
+            let pinned_table = write_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+            index.range(..=(now, u64::MAX))
+                .expect("Failed to iterate expiry index")
+                .filter_map(|item| item.ok())
+                .map(|(key, _)| key.value().1)
+                .filter(|id| !matches!(pinned_table.get(*id), Ok(Some(_))))
+                .collect()
+        };
+        for id in &items_to_delete {
+            self.remove_item(&write_txn, *id);
+        }
+        write_txn.commit().expect("Failed to commit transaction");
+        items_to_delete.len()
+    }
+
+    // 클립보드 기록 전체를 휴지통으로 옮김 (메모는 유지), 옮긴 개수 반환
+    pub fn clear_history(&self, include_pinned: bool) -> usize {
+        self.delete_range(None, None, include_pinned)
+    }
+
+    // 텍스트 항목의 본문을 수정, 항목이 없거나 텍스트 항목이 아니면 false
+    // HTML은 수정한 본문과 맞지 않으므로 버리고, 복사 시각과 고정, 메모, 태그, 사용 횟수는 유지한다.
+    // 크기 제한은 새로 복사한 항목과 같이 적용한다.
+    pub fn update_item(&self, id: u64, text: &str) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let old = {
+            let table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+            table.get(id).expect("Failed to get clipboard data")
+                .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()))
+        };
+        let Some(old) = old.filter(|record| record.kind == ClipboardKind::Text) else {
+            return false;
+        };
+        let mut record = ClipboardRecord::text(old.timestamp, text);
+        record.pinned = old.pinned;
+        record.memo_id = old.memo_id;
+        record.use_count = old.use_count;
+        record.sensitive = old.sensitive;
+        record.expires_at = old.expires_at;
+        if !self.prepare_record(id, &mut record, &mut None) {
+            return false;
+        }
+
+        // 이전 내용의 해시 색인, 검색 색인과 크기를 되돌린 뒤 같은 id로 다시 저장
+        {
+            let mut hash_index = write_txn.open_table(HASH_INDEX_TABLE).expect("Failed to open hash index table");
+            let hash = crypto::index_hash(self.cipher(), &old.hash);
+            let indexed = hash_index.get(hash.as_str()).expect("Failed to read hash index").map(|value| value.value());
+            if indexed == Some(id) {
+                hash_index.remove(hash.as_str()).expect("Failed to update hash index");
+            }
+        }
+        if !old.sensitive {
+            self.unindex_document(&write_txn, id, &old.content);
+        }
+        set_total_bytes(&write_txn, total_bytes(&write_txn).saturating_sub(old.size));
+        self.insert_item(&write_txn, id, &record, None);
+        write_txn.commit().expect("Failed to commit transaction");
+        // 외부 저장 파일은 커밋한 뒤에 바꿔 넣거나 지움
+        if record.external {
+            self.commit_external(id);
+        } else if old.external {
+            storage::remove_external(&self.blob_dir(), id);
+        }
+        true
+    }
+
+    // 고정되지 않은 항목 중 가장 이른 만료 시각(ms)
+    pub fn next_expiry(&self) -> Option<i64> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        let index = read_txn.open_table(EXPIRY_INDEX_TABLE).expect("Failed to open expiry index table");
+        let pinned_table = read_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+        index.iter()
+            .expect("Failed to iterate expiry index")
+            .filter_map(|item| item.ok())
+            .map(|(key, _)| key.value())
+            .find(|(_, id)| !matches!(pinned_table.get(*id), Ok(Some(_))))
+            .map(|(expires_at, _)| expires_at)
+    }
+
+    // 레코드를 저장하고 시간 인덱스에 등록
+    fn insert_item(&self, write_txn: &WriteTransaction, id: u64, record: &ClipboardRecord, png: Option<&[u8]>) {
+        let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+        table.insert(id, record.encode(self.cipher()).as_slice())
+            .expect("Failed to insert clipboard data");
+
+        let mut index = write_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+        index.insert((record.timestamp, id), ()).expect("Failed to update time index");
+        let mut hash_index = write_txn.open_table(HASH_INDEX_TABLE).expect("Failed to open hash index table");
+        hash_index.insert(crypto::index_hash(self.cipher(), &record.hash).as_str(), id)
+            .expect("Failed to update hash index");
+        if record.pinned {
+            let mut pinned_table = write_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+            pinned_table.insert(id, ()).expect("Failed to update pinned table");
+        }
+        if let Some(expires_at) = record.expires_at {
+            let mut expiry_index = write_txn.open_table(EXPIRY_INDEX_TABLE).expect("Failed to open expiry index table");
+            expiry_index.insert((expires_at, id), ()).expect("Failed to update expiry index");
+        }
+        if !record.sensitive {
+            self.index_document(write_txn, id, &record.content);
+        }
+        set_total_bytes(write_txn, total_bytes(write_txn) + record.size);
+
+        if let Some(png) = png {
+            let mut image_table = write_txn.open_table(IMAGE_TABLE).expect("Failed to open image table");
+            image_table.insert(id, crypto::seal(self.cipher(), png.to_vec()).as_slice())
+                .expect("Failed to insert image data");
+        }
+    }
+
+    // 레코드와 이미지, 시간 인덱스 항목을 함께 삭제하고 삭제된 레코드를 반환
+    fn remove_item(&self, write_txn: &WriteTransaction, id: u64) -> Option<ClipboardRecord> {
+        let record = self.unlink_item(write_txn, id)?;
+        self.remove_content(write_txn, id, &record);
+        Some(record)
+    }
+
+    // 태그와 컬렉션을 기억해 두고 항목을 휴지통으로 옮김 (이미지와 외부 저장 파일은 그대로), 항목이 없으면 false
+    fn trash_item(&self, write_txn: &WriteTransaction, id: u64, deleted_at: i64) -> bool {
+        let tags = labels::item_labels(write_txn, Label::Tag, id);
+        let collections = labels::item_labels(write_txn, Label::Collection, id);
+        let Some(record) = self.unlink_item(write_txn, id) else {
+            return false;
+        };
+        let entry = TrashEntry { deleted_at, tags, collections, item: TrashedItem::Clipboard { record } };
+        trash::put(write_txn, self.cipher(), id, &entry);
+        true
+    }
+
+    // 레코드와 색인에서 항목을 지우고 삭제된 레코드를 반환 (이미지와 외부 저장 파일은 남김)
+    fn unlink_item(&self, write_txn: &WriteTransaction, id: u64) -> Option<ClipboardRecord> {
+        let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+        let record = table.remove(id).expect("Failed to remove clipboard data")
+            .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()))?;
+
+        let mut index = write_txn.open_table(TIME_INDEX_TABLE).expect("Failed to open time index table");
+        index.remove((record.timestamp, id)).expect("Failed to update time index");
+        // 같은 내용의 더 최근 항목을 가리키고 있으면 그대로 둠 (DedupMode::Off로 저장된 중복)
+        let mut hash_index = write_txn.open_table(HASH_INDEX_TABLE).expect("Failed to open hash index table");
+        let hash = crypto::index_hash(self.cipher(), &record.hash);
+        let indexed = hash_index.get(hash.as_str()).expect("Failed to read hash index").map(|value| value.value());
+        if indexed == Some(id) {
+            hash_index.remove(hash.as_str()).expect("Failed to update hash index");
+        }
+        let mut pinned_table = write_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+        pinned_table.remove(id).expect("Failed to update pinned table");
+        if let Some(expires_at) = record.expires_at {
+            let mut expiry_index = write_txn.open_table(EXPIRY_INDEX_TABLE).expect("Failed to open expiry index table");
+            expiry_index.remove((expires_at, id)).expect("Failed to update expiry index");
+        }
+        // 붙어 있던 메모는 연결만 끊고 일반 메모로 남김
+        if let Some(memo_id) = record.memo_id {
+            let mut link_table = write_txn.open_table(MEMO_LINK_TABLE).expect("Failed to open memo link table");
+            link_table.remove(memo_id).expect("Failed to update memo link table");
+        }
+        labels::remove_item(write_txn, id);
+        if !record.sensitive {
+            self.unindex_document(write_txn, id, &record.content);
+        }
+        set_total_bytes(write_txn, total_bytes(write_txn).saturating_sub(record.size));
+        Some(record)
+    }
+
+    fn remove_content(&self, write_txn: &WriteTransaction, id: u64, record: &ClipboardRecord) {
+        let mut image_table = write_txn.open_table(IMAGE_TABLE).expect("Failed to open image table");
+        image_table.remove(id).expect("Failed to remove image data");
+        if record.external {
+            storage::remove_external(&self.blob_dir(), id);
+        }
+    }
+
+    // 메모 관련 메서드들 (id는 클립보드 항목과 같은 공간에서 발급)
+    #[allow(dead_code)]
+    pub fn add_memo(&self, memo: &str) -> u64 {
+        self.add_memo_record(&MemoRecord::new(memo)).expect("Clipboard database is locked")
+    }
+
+    // 제목, 폴더, 설명이 있는 메모 추가 (빈 값과 폴더 경로는 MemoEdit와 같이 정리)
+    // 잠겨 있으면 암호화하지 않은 채로 저장하게 되므로 저장하지 않고 None
+    pub fn add_memo_record(&self, record: &MemoRecord) -> Option<u64> {
+        if self.is_locked() {
+            warn!("Clipboard database is locked; memo not saved");
+            return None;
+        }
+        let mut memo = MemoRecord::new(&record.memo);
+        MemoEdit {
+            memo: None,
+            title: record.title.clone(),
+            folder: record.folder.clone(),
+            description: record.description.clone(),
+        }.apply(&mut memo);
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let id = allocate_id(&write_txn);
+        self.write_memo(&write_txn, id, &memo);
+        write_txn.commit().expect("Failed to commit transaction");
+        Some(id)
+    }
+
+    #[allow(dead_code)]
+    pub fn get_memo(&self, id: u64) -> Option<String> {
+        self.get_memo_item(id).map(|item| item.memo)
+    }
+
+    // 본문만 바꿈 (제목, 폴더, 설명은 그대로)
+    #[allow(dead_code)]
+    pub fn update_memo(&self, id: u64, memo: &str) {
+        self.edit_memo(id, &MemoEdit { memo: Some(memo.to_string()), ..Default::default() });
+    }
+
+    // 메모가 없거나 잠겨 있으면 false
+    pub fn edit_memo(&self, id: u64, edit: &MemoEdit) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let Some(mut record) = self.read_memo(&write_txn, id) else {
+            return false;
+        };
+        edit.apply(&mut record);
+        self.write_memo(&write_txn, id, &record);
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    // 메모를 폴더로 옮김, folder가 None이거나 빈 경로면 최상위, 메모가 없거나 잠겨 있으면 false
+    pub fn move_memo(&self, id: u64, folder: Option<&str>) -> bool {
+        self.edit_memo(id, &MemoEdit { folder: Some(folder.unwrap_or_default().to_string()), ..Default::default() })
+    }
+
+    // 메모가 들어 있는 폴더들 (상위 폴더 포함, 경로 순), 최상위 메모는 세지 않음
+    pub fn memo_folders(&self) -> Vec<MemoFolder> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+        let mut counts: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+        let folders = memo_table.iter()
+            .expect("Failed to iterate memo table")
+            .filter_map(|item| item.ok())
+            .filter_map(|(_, value)| MemoRecord::decode(value.value(), self.cipher())?.folder);
+        for folder in folders {
+            let parts: Vec<&str> = folder.split('/').collect();
+            for depth in 1..=parts.len() {
+                *counts.entry(parts[..depth].join("/")).or_default() += 1;
+            }
+        }
+        counts.into_iter()
+            .map(|(path, count)| MemoFolder { path, count })
+            .collect()
+    }
+
+    /**
+     * 폴더 이름을 바꾸거나 다른 폴더 아래로 옮김 (하위 폴더 포함)
+     * to가 이미 있는 폴더면 합쳐지고, 빈 경로면 폴더 안의 메모가 최상위로 나온다.
+     * 옮긴 메모 수를 반환하며, 자기 하위 폴더로는 옮길 수 없어 0을 반환한다.
+     */
+    pub fn rename_memo_folder(&self, from: &str, to: &str) -> usize {
+        let (Some(from), to) = (normalize_folder(from), normalize_folder(to)) else {
+            return 0;
+        };
+        if self.is_locked() || to.as_deref().is_some_and(|to| to == from || to.starts_with(&format!("{}/", from))) {
+            return 0;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let memos: Vec<(u64, MemoRecord)> = {
+            let memo_table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+            memo_table.iter()
+                .expect("Failed to iterate memo table")
+                .filter_map(|item| item.ok())
+                .filter_map(|(key, value)| Some((key.value(), MemoRecord::decode(value.value(), self.cipher())?)))
+                .filter(|(_, record)| record.folder.as_deref()
+                    .is_some_and(|folder| folder == from || folder.starts_with(&format!("{}/", from))))
+                .collect()
+        };
+        for (id, mut record) in memos.iter().cloned() {
+            let rest = &record.folder.as_deref().unwrap_or_default()[from.len()..];
+            record.folder = normalize_folder(&format!("{}{}", to.as_deref().unwrap_or_default(), rest));
+            self.write_memo(&write_txn, id, &record);
+        }
+        write_txn.commit().expect("Failed to commit transaction");
+        memos.len()
+    }
+
+    // 메모를 휴지통으로 옮김
+    pub fn delete_memo(&self, id: u64) {
+        if self.is_locked() {
+            return;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        self.trash_memo(&write_txn, id, now_millis());
+        write_txn.commit().expect("Failed to commit transaction");
+    }
+
+    // 클립보드 항목에 메모를 붙이거나 이미 붙은 메모를 수정, 항목이 없으면 None
+    // 붙은 메모는 일반 메모와 같은 테이블에 저장되므로 검색과 태그도 그대로 쓸 수 있다.
+    pub fn set_item_memo(&self, id: u64, memo: &str) -> Option<u64> {
+        if self.is_locked() {
+            return None;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let memo_id = {
+            let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+            let mut record = table.get(id).expect("Failed to get clipboard data")
+                .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()))?;
+            match record.memo_id {
+                Some(memo_id) => memo_id,
+                None => {
+                    let memo_id = allocate_id(&write_txn);
+                    record.memo_id = Some(memo_id);
+                    table.insert(id, record.encode(self.cipher()).as_slice())
+                        .expect("Failed to update clipboard data");
+                    let mut link_table = write_txn.open_table(MEMO_LINK_TABLE).expect("Failed to open memo link table");
+                    link_table.insert(memo_id, id).expect("Failed to update memo link table");
+                    memo_id
+                }
+            }
+        };
+        // 이미 붙은 메모는 본문만 바꿈
+        let mut record = self.read_memo(&write_txn, memo_id).unwrap_or_default();
+        record.memo = memo.to_string();
+        self.write_memo(&write_txn, memo_id, &record);
+        write_txn.commit().expect("Failed to commit transaction");
+        Some(memo_id)
+    }
+
+    // 클립보드 항목에 붙은 메모를 휴지통으로 옮김, 붙은 메모가 없으면 false
+    pub fn remove_item_memo(&self, id: u64) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        let Some(memo_id) = self.read_record(id).and_then(|record| record.memo_id) else {
+            return false;
+        };
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        self.trash_memo(&write_txn, memo_id, now_millis());
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    fn read_memo(&self, write_txn: &WriteTransaction, id: u64) -> Option<MemoRecord> {
+        let table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+        let value = table.get(id).expect("Failed to get memo")?;
+        MemoRecord::decode(value.value(), self.cipher())
+    }
+
+    // 메모를 저장하고 검색 색인과 수정 시각 갱신, 새 메모는 직접 정한 순서에서 맨 위에 둔다
+    // 본문이 바뀌면 바뀌기 전 본문을 이전 버전으로 남기고, 폴더만 옮긴 것은 수정으로 치지 않는다
+    fn write_memo(&self, write_txn: &WriteTransaction, id: u64, record: &MemoRecord) {
+        let previous = {
+            let mut table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+            table.insert(id, record.encode(self.cipher()).as_slice()).expect("Failed to insert memo")
+                .and_then(|previous| MemoRecord::decode(previous.value(), self.cipher()))
+        };
+        let now = now_millis();
+        let mut meta_table = write_txn.open_table(MEMO_META_TABLE).expect("Failed to open memo meta table");
+        let existing = meta_table.get(id).expect("Failed to read memo meta")
+            .map(|value| MemoMeta::from_value(value.value()));
+        let edited = previous.as_ref().is_none_or(|previous| MemoRecord { folder: None, ..previous.clone() }
+            != MemoRecord { folder: None, ..record.clone() });
+        if let Some(previous) = &previous {
+            self.unindex_document(write_txn, id, &previous.search_text());
+            if previous.memo != record.memo {
+                let saved_at = existing.map_or(now, |meta| meta.updated_at);
+                revision::push(write_txn, self.cipher(), id, &previous.memo, saved_at, self.max_memo_revisions);
+            }
+        }
+        self.index_document(write_txn, id, &record.search_text());
+
+        let meta = match existing {
+            Some(meta) if !edited => meta,
+            Some(meta) => MemoMeta { updated_at: now, ..meta },
+            None => MemoMeta { created_at: now, updated_at: now, position: next_top_position(write_txn, &meta_table) },
+        };
+        meta_table.insert(id, meta.value()).expect("Failed to update memo meta");
+    }
+
+    // 메모를 색인과 태그에서 지워 휴지통으로 옮기고 클립보드 항목에 붙어 있었으면 연결도 끊음
+    fn trash_memo(&self, write_txn: &WriteTransaction, id: u64, deleted_at: i64) {
+        let tags = labels::item_labels(write_txn, Label::Tag, id);
+        let collections = labels::item_labels(write_txn, Label::Collection, id);
+        let previous = {
+            let mut table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+            table.remove(id).expect("Failed to remove memo")
+                .and_then(|previous| MemoRecord::decode(previous.value(), self.cipher()))
+        };
+        if let Some(previous) = &previous {
+            self.unindex_document(write_txn, id, &previous.search_text());
+            labels::remove_item(write_txn, id);
+        }
+
+        let item_id = {
+            let mut link_table = write_txn.open_table(MEMO_LINK_TABLE).expect("Failed to open memo link table");
+            link_table.remove(id).expect("Failed to update memo link table").map(|value| value.value())
+        };
+        let meta = {
+            let mut meta_table = write_txn.open_table(MEMO_META_TABLE).expect("Failed to open memo meta table");
+            meta_table.remove(id).expect("Failed to remove memo meta").map(|value| MemoMeta::from_value(value.value()))
+        };
+        if let Some(record) = previous {
+            let entry = TrashEntry { deleted_at, tags, collections, item: TrashedItem::Memo { record, item_id, meta } };
+            trash::put(write_txn, self.cipher(), id, &entry);
+        }
+        if let Some(item_id) = item_id {
+            self.set_record_memo(write_txn, item_id, None);
+        }
+    }
+
+    // 클립보드 항목의 memo_id를 바꿈, 항목이 없으면 false
+    fn set_record_memo(&self, write_txn: &WriteTransaction, item_id: u64, memo_id: Option<u64>) -> bool {
+        let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+        let record = table.get(item_id).expect("Failed to get clipboard data")
+            .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()));
+        let Some(mut record) = record else {
+            return false;
+        };
+        record.memo_id = memo_id;
+        table.insert(item_id, record.encode(self.cipher()).as_slice())
+            .expect("Failed to update clipboard data");
+        true
+    }
+
+    // 남아 있는 메모와 클립보드 항목이 둘 다 다른 쪽과 연결되어 있지 않으면 연결, 연결하지 못하면 false
+    fn link_memo(&self, write_txn: &WriteTransaction, memo_id: u64, item_id: u64) -> bool {
+        let linkable = {
+            let memo_table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+            let link_table = write_txn.open_table(MEMO_LINK_TABLE).expect("Failed to open memo link table");
+            let table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+            let item_free = table.get(item_id).expect("Failed to get clipboard data")
+                .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()))
+                .is_some_and(|record| record.memo_id.is_none());
+            matches!(memo_table.get(memo_id), Ok(Some(_)))
+                && !matches!(link_table.get(memo_id), Ok(Some(_)))
+                && item_free
+        };
+        if !linkable {
+            return false;
+        }
+        self.set_record_memo(write_txn, item_id, Some(memo_id));
+        let mut link_table = write_txn.open_table(MEMO_LINK_TABLE).expect("Failed to open memo link table");
+        link_table.insert(memo_id, item_id).expect("Failed to update memo link table");
+        true
+    }
+
+    // 휴지통의 항목들 (최근에 삭제한 항목부터)
+    pub fn list_trash(&self) -> Vec<TrashItem> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        trash::list(&read_txn, self.cipher())
+    }
+
+    /**
+     * 휴지통의 클립보드 항목이나 메모를 같은 id로 복원, 없으면 false
+     * 태그는 다시 붙이고 컬렉션은 아직 있는 것만 다시 담는다.
+     * 붙어 있던 메모(또는 메모가 붙어 있던 항목)가 남아 있고 다른 쪽과 연결되지 않았으면 다시 연결하고,
+     * 아직 휴지통에 있으면 나중에 복원될 때 연결되도록 그 항목에 기록해 둔다.
+     * 클립보드 항목은 원래 복사 시각으로 돌아가며 max_items를 넘으면 오래된 항목이 정리된다.
+     * 같은 내용이 그 사이 다시 복사되어 있으면 해시 색인은 그 항목을 계속 가리키고,
+     * DedupMode::Global이면 새로 만들지 않고 기존 항목에 합친다 (see merge_restored).
+     */
+    pub fn restore_from_trash(&self, id: u64) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let Some(entry) = trash::take(&write_txn, self.cipher(), id) else {
+            return false;
+        };
+        let mut target = id;
+        let mut dropped_external = false;
+        match entry.item {
+            TrashedItem::Clipboard { mut record } => {
+                let memo_id = record.memo_id.take();
+                let existing = self.live_duplicate(&write_txn, &record.hash);
+                match existing {
+                    Some(existing) if self.dedup_mode == DedupMode::Global => {
+                        self.merge_restored(&write_txn, existing, id, &record);
+                        target = existing;
+                        dropped_external = record.external;
+                        info!("Restored item {} merged into clipboard item {} with the same content", id, existing);
+                    }
+                    _ => {
+                        self.insert_item(&write_txn, id, &record, None);
+                        if let Some(existing) = existing {
+                            let mut hash_index = write_txn.open_table(HASH_INDEX_TABLE).expect("Failed to open hash index table");
+                            hash_index.insert(crypto::index_hash(self.cipher(), &record.hash).as_str(), existing)
+                                .expect("Failed to update hash index");
+                        }
                     }
                 }
+                if let Some(memo_id) = memo_id && !self.link_memo(&write_txn, memo_id, target) {
+                    trash::update(&write_txn, self.cipher(), memo_id, |trashed| {
+                        if let TrashedItem::Memo { item_id, .. } = &mut trashed.item {
+                            *item_id = Some(target);
+                        }
+                    });
+                }
+            }
+            TrashedItem::Memo { record, item_id, meta } => {
+                self.write_memo(&write_txn, id, &record);
+                // 만든/수정한 시각과 순서도 삭제하기 전으로
+                if let Some(meta) = meta {
+                    let mut meta_table = write_txn.open_table(MEMO_META_TABLE).expect("Failed to open memo meta table");
+                    meta_table.insert(id, meta.value()).expect("Failed to update memo meta");
+                    lower_top_position(&write_txn, meta.position);
+                }
+                if let Some(item_id) = item_id && !self.link_memo(&write_txn, id, item_id) {
+                    trash::update(&write_txn, self.cipher(), item_id, |trashed| {
+                        if let TrashedItem::Clipboard { record } = &mut trashed.item {
+                            record.memo_id = Some(id);
+                        }
+                    });
+                }
+            }
+        }
+        for tag in &entry.tags {
+            labels::add(&write_txn, Label::Tag, tag, target);
+        }
+        {
+            let collection_table = write_txn.open_table(labels::COLLECTION_TABLE).expect("Failed to open collection table");
+            for collection in &entry.collections {
+                if matches!(collection_table.get(collection.as_str()), Ok(Some(_))) {
+                    labels::add(&write_txn, Label::Collection, collection, target);
+                }
+            }
+        }
+        let removed = self.cleanup_old_items(&write_txn, target);
+        write_txn.commit().expect("Failed to commit transaction");
+        if dropped_external {
+            storage::remove_external(&self.blob_dir(), id);
+        }
+        if removed > 0 {
+            info!("Cleaned up {} old clipboard items after restoring item {}", removed, id);
+        }
+        true
+    }
+
+    // 해시 색인이 가리키는 같은 내용의 항목 (색인만 남고 항목이 없으면 None)
+    fn live_duplicate(&self, write_txn: &WriteTransaction, hash: &str) -> Option<u64> {
+        let hash_index = write_txn.open_table(HASH_INDEX_TABLE).expect("Failed to open hash index table");
+        let id = hash_index.get(crypto::index_hash(self.cipher(), hash).as_str())
+            .expect("Failed to read hash index")?
+            .value();
+        let table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+        matches!(table.get(id), Ok(Some(_))).then_some(id)
+    }
+
+    // 휴지통에서 복원한 항목(id)을 같은 내용의 기존 항목에 합침
+    // 고정, 사용 횟수, 비밀 정보 플래그와 만료 시각을 옮기고 복사 시각은 기존 항목을 따름
+    // 복원한 항목의 이미지는 지우며 외부 저장 파일은 호출한 쪽에서 커밋한 뒤에 지움
+    fn merge_restored(&self, write_txn: &WriteTransaction, existing: u64, id: u64, restored: &ClipboardRecord) {
+        let mut table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open table");
+        let record = table.get(existing).expect("Failed to get clipboard data")
+            .and_then(|value| ClipboardRecord::decode(value.value(), self.cipher()));
+        if let Some(mut record) = record {
+            record.use_count += restored.use_count;
+            if restored.pinned && !record.pinned {
+                let mut pinned_table = write_txn.open_table(PINNED_TABLE).expect("Failed to open pinned table");
+                pinned_table.insert(existing, ()).expect("Failed to update pinned table");
+                record.pinned = true;
+            }
+            self.merge_flags(write_txn, existing, &mut record, restored);
+            table.insert(existing, record.encode(self.cipher()).as_slice())
+                .expect("Failed to update clipboard data");
+        }
+        let mut image_table = write_txn.open_table(IMAGE_TABLE).expect("Failed to open image table");
+        image_table.remove(id).expect("Failed to remove image data");
+    }
+
+    // 휴지통의 항목을 완전히 삭제 (이미지와 외부 저장 파일 포함), 없으면 false
+    pub fn delete_from_trash(&self, id: u64) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let deleted = self.purge_trash_entry(&write_txn, id);
+        write_txn.commit().expect("Failed to commit transaction");
+        deleted
+    }
+
+    // cutoff(ms)보다 먼저 삭제한 항목을 휴지통에서 비움 (None이면 전부), 비운 개수 반환
+    pub fn purge_trash(&self, cutoff: Option<i64>) -> usize {
+        if self.is_locked() {
+            return 0;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let ids = trash::ids_before(&write_txn, cutoff);
+        for id in &ids {
+            self.purge_trash_entry(&write_txn, *id);
+        }
+        write_txn.commit().expect("Failed to commit transaction");
+        ids.len()
+    }
+
+    fn purge_trash_entry(&self, write_txn: &WriteTransaction, id: u64) -> bool {
+        let Some(entry) = trash::take(write_txn, self.cipher(), id) else {
+            return false;
+        };
+        match &entry.item {
+            TrashedItem::Clipboard { record } => self.remove_content(write_txn, id, record),
+            TrashedItem::Memo { .. } => revision::remove_all(write_txn, id),
+        }
+        true
+    }
+
+    // 암호화된 DB는 검색 색인을 저장하지 않음 (search.rs)
+    fn index_document(&self, write_txn: &WriteTransaction, id: u64, text: &str) {
+        if !self.encrypted {
+            search::index_document(write_txn, self.tokenizer.as_ref(), id, text);
+        }
+    }
+
+    fn unindex_document(&self, write_txn: &WriteTransaction, id: u64, text: &str) {
+        if !self.encrypted {
+            search::unindex_document(write_txn, self.tokenizer.as_ref(), id, text);
+        }
+    }
+
+    // 클립보드 항목과 메모를 함께 검색 (종류별로 최대 count개, filter에 맞는 항목만)
+    pub fn search(&self, query: &str, count: Option<usize>, filter: &LabelFilter) -> SearchResults {
+        let matches = {
+            let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+            search::search(&read_txn, self.tokenizer.as_ref(), query, self.cipher())
+        };
+        self.search_results(matches, count, filter, |text| search::highlights(self.tokenizer.as_ref(), text, query))
+    }
+
+    // 색인 대신 모든 항목을 훑어 검색어 글자가 순서대로 나오는 항목을 찾음 (fzf 방식 점수)
+    pub fn fuzzy_search(&self, query: &str, count: Option<usize>, filter: &LabelFilter) -> SearchResults {
+        let matches = {
+            let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+            search::fuzzy_search(&read_txn, query, self.cipher())
+        };
+        self.search_results(matches, count, filter, |text| {
+            matcher::fuzzy_match(query, text).map(|(_, highlights)| highlights).unwrap_or_default()
+        })
+    }
+
+    // 정규식 검색, 결과는 최근 항목이 먼저이며 잘못되었거나 너무 큰 패턴은 에러
+    pub fn regex_search(&self, pattern: &str, count: Option<usize>, filter: &LabelFilter) -> Result<SearchResults, regex::Error> {
+        let regex = matcher::compile_regex(pattern)?;
+        let matches = {
+            let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+            search::regex_search(&read_txn, &regex, self.cipher())
+        };
+        Ok(self.search_results(matches, count, filter, |text| matcher::regex_highlights(&regex, text)))
+    }
+
+    // (id, 점수) 순서대로 항목을 읽어 종류별로 최대 count개씩 담음
+    fn search_results(
+        &self,
+        matches: Vec<(u64, f64)>,
+        count: Option<usize>,
+        filter: &LabelFilter,
+        highlights: impl Fn(&str) -> Vec<[usize; 2]>,
+    ) -> SearchResults {
+        let count = count.unwrap_or(usize::MAX);
+        let mut results = SearchResults { clipboard: Vec::new(), memos: Vec::new() };
+        for (id, score) in matches {
+            if results.clipboard.len() >= count && results.memos.len() >= count {
+                break;
+            }
+            // id는 클립보드 항목과 메모 중 한쪽에만 존재
+            if results.clipboard.len() < count && let Some(item) = self.get_clipboard_item(id) {
+                if filter.matches(&item.tags, &item.collections) {
+                    let highlights = highlights(&item.content);
+                    results.clipboard.push(SearchHit { item, score, highlights });
+                }
+            } else if results.memos.len() < count
+                && let Some(item) = self.get_memo_item(id)
+                && filter.matches(&item.tags, &item.collections)
+            {
+                let highlights = highlights(&item.memo);
+                results.memos.push(SearchHit { item, score, highlights });
+            }
+        }
+        results
+    }
+
+    // 태그 관련 메서드들 (클립보드 항목과 메모 모두 같은 id로 태그를 붙임)
+
+    // 항목에 태그를 붙임, 항목이 없으면 false
+    pub fn add_tags(&self, id: u64, tags: &[String]) -> bool {
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        if !item_exists(&write_txn, id) {
+            return false;
+        }
+        for tag in tags.iter().map(|tag| labels::normalize(Label::Tag, tag)).filter(|tag| !tag.is_empty()) {
+            labels::add(&write_txn, Label::Tag, &tag, id);
+        }
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    // 항목의 태그를 tags로 교체, 항목이 없으면 false
+    pub fn set_tags(&self, id: u64, tags: &[String]) -> bool {
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        if !item_exists(&write_txn, id) {
+            return false;
+        }
+        let tags: Vec<String> = tags.iter()
+            .map(|tag| labels::normalize(Label::Tag, tag))
+            .filter(|tag| !tag.is_empty())
+            .collect();
+        labels::set(&write_txn, Label::Tag, id, &tags);
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    // 항목에서 태그를 뗌, 붙어 있지 않았으면 false
+    pub fn remove_tag(&self, id: u64, tag: &str) -> bool {
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let removed = labels::remove(&write_txn, Label::Tag, &labels::normalize(Label::Tag, tag), id);
+        write_txn.commit().expect("Failed to commit transaction");
+        removed
+    }
+
+    // 사용 중인 태그와 태그별 항목 수 (이름 순)
+    pub fn list_tags(&self) -> Vec<LabelCount> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        labels::counts(&read_txn, Label::Tag)
+            .into_iter()
+            .map(|(name, count)| LabelCount { name, count })
+            .collect()
+    }
+
+    // 태그 이름 변경 (to가 이미 있으면 합쳐짐), 바뀐 항목 수 반환
+    pub fn rename_tag(&self, from: &str, to: &str) -> usize {
+        let (from, to) = (labels::normalize(Label::Tag, from), labels::normalize(Label::Tag, to));
+        if to.is_empty() || from == to {
+            return 0;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let renamed = labels::rename(&write_txn, Label::Tag, &from, &to);
+        write_txn.commit().expect("Failed to commit transaction");
+        renamed
+    }
+
+    // 태그를 모든 항목에서 뗌, 뗀 항목 수 반환
+    pub fn delete_tag(&self, tag: &str) -> usize {
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let deleted = labels::delete(&write_txn, Label::Tag, &labels::normalize(Label::Tag, tag));
+        write_txn.commit().expect("Failed to commit transaction");
+        deleted
+    }
+
+    // 컬렉션 관련 메서드들 (이름을 먼저 만든 뒤 항목을 담음)
+
+    // 이름이 비었거나 이미 있으면 false
+    pub fn create_collection(&self, name: &str) -> bool {
+        let name = labels::normalize(Label::Collection, name);
+        if name.is_empty() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        {
+            let mut table = write_txn.open_table(labels::COLLECTION_TABLE).expect("Failed to open collection table");
+            if table.get(name.as_str()).expect("Failed to read collection").is_some() {
+                return false;
+            }
+            table.insert(name.as_str(), now_millis()).expect("Failed to create collection");
+        }
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    // 모든 컬렉션과 담긴 항목 수 (이름 순, 빈 컬렉션 포함)
+    pub fn list_collections(&self) -> Vec<LabelCount> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        let counts = labels::counts(&read_txn, Label::Collection);
+        let table = read_txn.open_table(labels::COLLECTION_TABLE).expect("Failed to open collection table");
+        table.iter()
+            .expect("Failed to iterate collections")
+            .filter_map(|item| item.ok())
+            .map(|(key, _)| {
+                let name = key.value().to_string();
+                let count = counts.get(&name).copied().unwrap_or(0);
+                LabelCount { name, count }
+            })
+            .collect()
+    }
+
+    // from이 없거나 to가 이미 있으면 false
+    pub fn rename_collection(&self, from: &str, to: &str) -> bool {
+        let (from, to) = (labels::normalize(Label::Collection, from), labels::normalize(Label::Collection, to));
+        if to.is_empty() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        {
+            let mut table = write_txn.open_table(labels::COLLECTION_TABLE).expect("Failed to open collection table");
+            if table.get(to.as_str()).expect("Failed to read collection").is_some() {
+                return false;
+            }
+            let Some(created) = table.remove(from.as_str()).expect("Failed to rename collection").map(|value| value.value()) else {
+                return false;
+            };
+            table.insert(to.as_str(), created).expect("Failed to rename collection");
+        }
+        labels::rename(&write_txn, Label::Collection, &from, &to);
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    // 컬렉션 삭제 (담긴 항목은 그대로), 없으면 false
+    pub fn delete_collection(&self, name: &str) -> bool {
+        let name = labels::normalize(Label::Collection, name);
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        {
+            let mut table = write_txn.open_table(labels::COLLECTION_TABLE).expect("Failed to open collection table");
+            if table.remove(name.as_str()).expect("Failed to delete collection").is_none() {
+                return false;
+            }
+        }
+        labels::delete(&write_txn, Label::Collection, &name);
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    // 컬렉션이나 항목이 없으면 false
+    pub fn add_to_collection(&self, name: &str, id: u64) -> bool {
+        let name = labels::normalize(Label::Collection, name);
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        {
+            let table = write_txn.open_table(labels::COLLECTION_TABLE).expect("Failed to open collection table");
+            if table.get(name.as_str()).expect("Failed to read collection").is_none() || !item_exists(&write_txn, id) {
+                return false;
+            }
+        }
+        labels::add(&write_txn, Label::Collection, &name, id);
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    // 컬렉션에 담겨 있지 않았으면 false
+    pub fn remove_from_collection(&self, name: &str, id: u64) -> bool {
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let removed = labels::remove(&write_txn, Label::Collection, &labels::normalize(Label::Collection, name), id);
+        write_txn.commit().expect("Failed to commit transaction");
+        removed
+    }
+
+    #[allow(dead_code)]
+    pub fn get_memo_items(&self, count: Option<usize>) -> Vec<MemoItem> {
+        self.query_memo_items(&MemoQuery { count, ..Default::default() })
+    }
+
+    // 폴더와 태그/컬렉션 조건에 맞는 메모를 sort 순서로 최대 count개 조회
+    pub fn query_memo_items(&self, query: &MemoQuery) -> Vec<MemoItem> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+        
+        // id 내림차순으로 읽어 두면 같은 시각이나 같은 순서일 때 최근에 추가된 메모가 먼저
+        let mut items: Vec<MemoItem> = memo_table.iter()
+            .expect("Failed to iterate memo table")
+            .rev()
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, value)| Some((key.value(), MemoRecord::decode(value.value(), self.cipher())?)))
+            .filter(|(_, record)| query.folder.as_deref()
+                .is_none_or(|folder| record.folder.as_deref().unwrap_or_default() == folder))
+            .map(|(id, record)| MemoItem::read(&read_txn, id, record))
+            .filter(|item| query.labels.matches(&item.tags, &item.collections))
+            .collect();
+        match query.sort {
+            MemoSort::Created => items.sort_by_key(|item| std::cmp::Reverse(item.created_at)),
+            MemoSort::Updated => items.sort_by_key(|item| std::cmp::Reverse(item.updated_at)),
+            MemoSort::Manual => items.sort_by_key(|item| item.position),
+        }
+        items.truncate(query.count.unwrap_or(usize::MAX));
+        items
+    }
+
+    /**
+     * 메모를 ids 순서대로 맨 위부터 배치 (sort=manual)
+     * ids에 없는 메모는 그 뒤에 기존 순서대로 놓이며, 같은 id가 여러 번 있으면 처음 위치를 쓴다.
+     * 메모가 아닌 id가 있으면 아무것도 바꾸지 않고 false
+     */
+    pub fn reorder_memos(&self, ids: &[u64]) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        {
+            let mut meta_table = write_txn.open_table(MEMO_META_TABLE).expect("Failed to open memo meta table");
+            let mut memos: Vec<(u64, MemoMeta)> = meta_table.iter()
+                .expect("Failed to iterate memo meta table")
+                .filter_map(|item| item.ok())
+                .map(|(key, value)| (key.value(), MemoMeta::from_value(value.value())))
+                .collect();
+            if ids.iter().any(|id| !memos.iter().any(|(memo_id, _)| memo_id == id)) {
+                return false;
+            }
+            memos.sort_by_key(|(id, meta)| (meta.position, std::cmp::Reverse(*id)));
+
+            let mut ordered: Vec<u64> = Vec::with_capacity(memos.len());
+            for id in ids.iter().chain(memos.iter().map(|(id, _)| id)) {
+                if !ordered.contains(id) {
+                    ordered.push(*id);
+                }
+            }
+            for (position, id) in ordered.iter().enumerate() {
+                let (_, meta) = memos.iter().find(|(memo_id, _)| memo_id == id).expect("Memo meta disappeared");
+                let meta = MemoMeta { position: position as i64, ..*meta };
+                meta_table.insert(*id, meta.value()).expect("Failed to update memo meta");
             }
         }
-        max_sequence + 1
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    // 메모의 이전 버전 (최근 버전부터), 메모가 없으면 None
+    pub fn memo_revisions(&self, id: u64) -> Option<Vec<MemoRevision>> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+        memo_table.get(id).expect("Failed to get memo")?;
+        Some(revision::list(&read_txn, self.cipher(), id))
+    }
+
+    // 메모를 이전 버전의 내용으로 되돌림, 메모나 버전이 없으면 false
+    // 되돌리기 전 내용도 새 버전으로 남으므로 되돌린 것을 다시 되돌릴 수 있다.
+    pub fn restore_memo_revision(&self, id: u64, revision: u64) -> bool {
+        if self.is_locked() {
+            return false;
+        }
+        let write_txn = self.db.begin_write().expect("Failed to begin write transaction");
+        let (Some(mut record), Some(memo)) = (self.read_memo(&write_txn, id), revision::get(&write_txn, self.cipher(), id, revision)) else {
+            return false;
+        };
+        record.memo = memo;
+        self.write_memo(&write_txn, id, &record);
+        write_txn.commit().expect("Failed to commit transaction");
+        true
+    }
+
+    #[allow(dead_code)]
+    pub fn get_memo_item(&self, id: u64) -> Option<MemoItem> {
+        let read_txn = self.db.begin_read().expect("Failed to begin read transaction");
+        let table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+        
+        let value = table.get(id).expect("Failed to get memo")?;
+        let record = MemoRecord::decode(value.value(), self.cipher())?;
+        Some(MemoItem::read(&read_txn, id, record))
     }
 }
 
+// id가 클립보드 항목이나 메모로 존재하는지
+fn item_exists(write_txn: &WriteTransaction, id: u64) -> bool {
+    let clipboard_table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+    let memo_table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+    matches!(clipboard_table.get(id), Ok(Some(_))) || matches!(memo_table.get(id), Ok(Some(_)))
+}
+
+// 클립보드 항목과 메모가 함께 쓰는 다음 id를 발급 (같은 트랜잭션에서 카운터 증가)
+fn allocate_id(write_txn: &WriteTransaction) -> u64 {
+    let mut metadata = write_txn.open_table(migration::METADATA_TABLE).expect("Failed to open metadata table");
+    let id = metadata.get(migration::NEXT_ID_KEY)
+        .expect("Failed to read next id")
+        .map(|value| value.value())
+        .unwrap_or(1);
+    metadata.insert(migration::NEXT_ID_KEY, id + 1).expect("Failed to write next id");
+    id
+}
+
+/**
+ * 새 메모를 맨 위에 놓을 position (지금까지 준 가장 작은 position - 1)
+ * 메모마다 메모 메타 테이블 전체를 훑지 않도록 MEMO_ORDER_TABLE에 기록해 두며 (음수이므로 u64인 METADATA_TABLE에는 두지 않음),
+ * 기록이 없는 DB에서만 한 번 훑어서 시작 값을 정한다.
+ * 기록된 값은 남아 있는 메모의 position보다 크지 않기만 하면 되므로 메모를 지우거나 재배치해도 고치지 않는다.
+ */
+fn next_top_position(write_txn: &WriteTransaction, meta_table: &redb::Table<u64, (i64, i64, i64)>) -> i64 {
+    let mut order_table = write_txn.open_table(MEMO_ORDER_TABLE).expect("Failed to open memo order table");
+    let top = order_table.get(TOP_POSITION_KEY)
+        .expect("Failed to read top memo position")
+        .map(|value| value.value())
+        .or_else(|| meta_table.iter()
+            .expect("Failed to iterate memo meta table")
+            .filter_map(|item| item.ok())
+            .map(|(_, value)| value.value().2)
+            .min());
+    let position = top.map_or(0, |top| top - 1);
+    order_table.insert(TOP_POSITION_KEY, position).expect("Failed to write top memo position");
+    position
+}
+
+// 휴지통에서 복원한 메모가 기록된 값보다 위에 있으면 기록을 낮춤
+fn lower_top_position(write_txn: &WriteTransaction, position: i64) {
+    let mut order_table = write_txn.open_table(MEMO_ORDER_TABLE).expect("Failed to open memo order table");
+    let top = order_table.get(TOP_POSITION_KEY)
+        .expect("Failed to read top memo position")
+        .map(|value| value.value());
+    if top.is_some_and(|top| position < top) {
+        order_table.insert(TOP_POSITION_KEY, position).expect("Failed to write top memo position");
+    }
+}
+
+// 저장된 클립보드 항목 크기의 합 (max_history_bytes 정리에 사용)
+fn total_bytes(write_txn: &WriteTransaction) -> u64 {
+    let metadata = write_txn.open_table(migration::METADATA_TABLE).expect("Failed to open metadata table");
+    metadata.get(migration::TOTAL_BYTES_KEY)
+        .expect("Failed to read total bytes")
+        .map(|value| value.value())
+        .unwrap_or(0)
+}
+
+fn set_total_bytes(write_txn: &WriteTransaction, bytes: u64) {
+    let mut metadata = write_txn.open_table(migration::METADATA_TABLE).expect("Failed to open metadata table");
+    metadata.insert(migration::TOTAL_BYTES_KEY, bytes).expect("Failed to write total bytes");
+}
+
+// 로컬 날짜 "YYYY-MM-DD"의 [자정, 다음날 자정) 범위 (ms)
+fn day_range(date_key: &str) -> Option<(i64, i64)> {
+    let date = chrono::NaiveDate::parse_from_str(date_key, "%Y-%m-%d").ok()?;
+    let midnight = |date: chrono::NaiveDate| -> Option<i64> {
+        let time = date.and_hms_opt(0, 0, 0)?.and_local_timezone(chrono::Local).earliest()?;
+        Some(time.timestamp_millis())
+    };
+    Some((midnight(date)?, midnight(date.succ_opt()?)?))
+}
+
+// timestamp(ms)의 로컬 날짜 "YYYY-MM-DD"
+fn local_date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp)
+        .map(|time| time.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn now_millis() -> i64 {
+    chrono::Local::now().timestamp_millis()
+}
+
 #[cfg(test)]
 mod tests;
\ No newline at end of file
@@ -0,0 +1,197 @@
+use std::ops::Bound;
+use serde::Serialize;
+use super::{ClipboardItem, ClipboardKind, LabelFilter};
+
+type KeyRange = (Bound<(i64, u64)>, Bound<(i64, u64)>);
+
+/**
+ * GET /clipboard 조회 조건
+ * 결과는 항상 최신순이며, 시간 인덱스의 (timestamp, id) 범위로 변환되어 조회된다.
+ *
+ * before/after: 해당 커서보다 오래된/최근의 항목 (커서 항목 자체는 제외)
+ * since/until: 복사 시각(ms) 범위 [since, until)
+ * offset: 조건에 맞는 항목 중 건너뛸 개수
+ * kinds: 비어 있으면 모든 종류
+ * pinned: 고정된 항목을 먼저 보여줄지, 고정된 항목만 볼지
+ * labels: 태그(모두 붙어 있어야 함)와 컬렉션 조건
+ */
+#[derive(Default, Clone, Debug)]
+pub struct ClipboardQuery {
+    pub count: Option<usize>,
+    pub offset: usize,
+    pub before: Option<Cursor>,
+    pub after: Option<Cursor>,
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    pub kinds: Vec<ClipboardKind>,
+    pub pinned: PinnedFilter,
+    pub labels: LabelFilter,
+}
+
+/**
+ * 고정된 항목 조회 방식
+ * First: 첫 페이지(커서와 offset 없음) 앞에 조건에 맞는 고정 항목 전체를 최신순으로 붙이고,
+ *        시간 순 목록에서는 고정 항목을 제외한다. 고정 항목은 count에 포함되지 않는다.
+ * Only: 고정된 항목만
+ */
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum PinnedFilter {
+    #[default]
+    Any,
+    First,
+    Only,
+}
+
+impl std::str::FromStr for PinnedFilter {
+    type Err = String;
+
+    fn from_str(pinned: &str) -> Result<Self, Self::Err> {
+        match pinned {
+            "any" => Ok(PinnedFilter::Any),
+            "first" => Ok(PinnedFilter::First),
+            "only" => Ok(PinnedFilter::Only),
+            _ => Err(format!("Unknown pinned filter: {}", pinned)),
+        }
+    }
+}
+
+/**
+ * GET /memo 조회 조건
+ * folder: Some("")이면 최상위 메모만, Some(path)이면 그 폴더에 바로 들어 있는 메모만 (하위 폴더 제외), None이면 전부
+ * labels: 태그(모두 붙어 있어야 함)와 컬렉션 조건
+ */
+#[derive(Default, Clone, Debug)]
+pub struct MemoQuery {
+    pub count: Option<usize>,
+    pub sort: MemoSort,
+    pub folder: Option<String>,
+    pub labels: LabelFilter,
+}
+
+// GET /memo 정렬 순서
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum MemoSort {
+    #[default]
+    Created, // 최근에 만든 메모부터
+    Updated, // 최근에 수정한 메모부터
+    Manual,  // 사용자가 정한 순서 (PUT /memo/order)
+}
+
+impl std::str::FromStr for MemoSort {
+    type Err = String;
+
+    fn from_str(sort: &str) -> Result<Self, Self::Err> {
+        match sort {
+            "created" => Ok(MemoSort::Created),
+            "updated" => Ok(MemoSort::Updated),
+            "manual" => Ok(MemoSort::Manual),
+            _ => Err(format!("Unknown memo sort: {}", sort)),
+        }
+    }
+}
+
+// 조회 결과 한 페이지, next_cursor는 같은 방향(before/after)으로 이어서 조회할 때 사용
+#[derive(Serialize, Clone)]
+pub struct ClipboardPage {
+    pub items: Vec<ClipboardItem>,
+    pub next_cursor: Option<String>,
+}
+
+// 시간 인덱스 키 (timestamp, id)를 가리키는 커서, 문자열 형식: "timestamp-id"
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Cursor {
+    pub timestamp: i64,
+    pub id: u64,
+}
+
+impl Cursor {
+    pub fn of(item: &ClipboardItem) -> Self {
+        Cursor { timestamp: item.timestamp, id: item.id }
+    }
+
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let (timestamp, id) = cursor.rsplit_once('-')?;
+        Some(Cursor {
+            timestamp: timestamp.parse().ok()?,
+            id: id.parse().ok()?,
+        })
+    }
+
+    fn key(&self) -> (i64, u64) {
+        (self.timestamp, self.id)
+    }
+}
+
+impl std::fmt::Display for Cursor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.timestamp, self.id)
+    }
+}
+
+impl ClipboardQuery {
+    // after만 지정된 경우 커서 바로 다음 항목부터 오래된 순으로 읽어야 함
+    pub(super) fn ascending(&self) -> bool {
+        self.after.is_some() && self.before.is_none()
+    }
+
+    // 고정 항목을 앞에 붙여야 하는 첫 페이지인지
+    pub(super) fn pinned_first_page(&self) -> bool {
+        self.pinned == PinnedFilter::First && self.before.is_none() && self.after.is_none() && self.offset == 0
+    }
+
+    // 시간 순 목록의 항목 조건 (시간 범위는 bounds()로 처리)
+    pub(super) fn matches(&self, item: &ClipboardItem) -> bool {
+        let pinned = match self.pinned {
+            PinnedFilter::Any => true,
+            PinnedFilter::First => !item.pinned,
+            PinnedFilter::Only => item.pinned,
+        };
+        pinned && self.matches_filters(item)
+    }
+
+    // 앞에 붙이는 고정 항목의 조건 (커서와 무관)
+    pub(super) fn matches_pinned(&self, item: &ClipboardItem) -> bool {
+        self.matches_filters(item)
+            && self.since.is_none_or(|since| item.timestamp >= since)
+            && self.until.is_none_or(|until| item.timestamp < until)
+    }
+
+    // 종류와 태그/컬렉션 조건
+    fn matches_filters(&self, item: &ClipboardItem) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&item.kind))
+            && self.labels.matches(&item.tags, &item.collections)
+    }
+
+    // 시간 인덱스 조회 범위, 조건이 서로 모순되면 None
+    pub(super) fn bounds(&self) -> Option<KeyRange> {
+        let (lower, upper) = (self.lower_bound(), self.upper_bound());
+        match (lower, upper) {
+            (Bound::Included(lower), Bound::Excluded(upper)) | (Bound::Excluded(lower), Bound::Excluded(upper))
+                if lower >= upper => None,
+            _ => Some((lower, upper)),
+        }
+    }
+
+    // since/after 중 더 좁은 쪽이 하한
+    fn lower_bound(&self) -> Bound<(i64, u64)> {
+        let since = self.since.map(|since| Bound::Included((since, 0)));
+        let after = self.after.map(|after| Bound::Excluded(after.key()));
+        match (since, after) {
+            (Some(Bound::Included(since)), Some(Bound::Excluded(after))) if since > after => Bound::Included(since),
+            (_, Some(after)) => after,
+            (Some(since), None) => since,
+            (None, None) => Bound::Unbounded,
+        }
+    }
+
+    // until/before 중 더 좁은 쪽이 상한 (둘 다 미포함)
+    fn upper_bound(&self) -> Bound<(i64, u64)> {
+        let until = self.until.map(|until| (until, 0));
+        let before = self.before.map(|before| before.key());
+        match (until, before) {
+            (Some(until), Some(before)) => Bound::Excluded(until.min(before)),
+            (Some(key), None) | (None, Some(key)) => Bound::Excluded(key),
+            (None, None) => Bound::Unbounded,
+        }
+    }
+}
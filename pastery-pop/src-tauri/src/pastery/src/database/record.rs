@@ -0,0 +1,186 @@
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use super::ClipboardKind;
+use super::crypto::{self, Cipher};
+use super::migration::V7_MEMO_MAGIC;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const COMPRESSION_LEVEL: i32 = 3;
+
+/**
+ * CLIPBOARD_TABLE에 저장되는 값
+ * 키(id)에 담지 않는 모든 메타데이터를 함께 보관한다.
+ * 새 필드는 #[serde(default)]로 추가하면 기존 레코드도 그대로 읽힌다.
+ *
+ * compressed가 켜진 레코드는 JSON 전체를 zstd로 압축해 저장한다.
+ * JSON은 항상 '{'로 시작하므로 읽을 때 zstd 매직 넘버로 구분하고,
+ * 플래그가 레코드 안에 있으므로 메타데이터만 고쳐 다시 저장해도 압축이 유지된다.
+ * 암호화된 DB에서는 (압축한) 바이트 전체를 암호화해 저장한다 (see crypto.rs).
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ClipboardRecord {
+    pub timestamp: i64,           // 복사 시각 (unix epoch, milliseconds)
+    pub size: u64,                // 저장된 내용의 바이트 크기
+    pub kind: ClipboardKind,
+    pub hash: String,             // 내용의 SHA-256 (hex)
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub memo_id: Option<u64>,     // 연결된 메모의 id
+    #[serde(default)]
+    pub use_count: u64,           // 다시 붙여넣거나 다시 복사된 횟수
+    #[serde(default)]
+    pub external: bool,           // 전체 내용이 DB 밖 파일에 저장됨 (storage.rs)
+    #[serde(default)]
+    pub compressed: bool,         // zstd로 압축해 저장
+    #[serde(default)]
+    pub sensitive: bool,          // 비밀 정보가 감지된 항목 (popup에서 내용을 가림, see secrets.rs)
+    #[serde(default)]
+    pub expires_at: Option<i64>,  // 이 시각(ms)이 지나면 삭제
+    pub content: String,
+    #[serde(default)]
+    pub html: Option<String>,
+    #[serde(default)]
+    pub files: Option<Vec<String>>,
+}
+
+impl ClipboardRecord {
+    pub fn text(timestamp: i64, content: &str) -> Self {
+        Self::build(timestamp, ClipboardKind::Text, content, None, None, content.as_bytes())
+    }
+
+    pub fn html(timestamp: i64, content: &str, html: &str) -> Self {
+        let payload = [content.as_bytes(), b"\0", html.as_bytes()].concat();
+        Self::build(timestamp, ClipboardKind::Text, content, Some(html.to_string()), None, &payload)
+    }
+
+    pub fn files(timestamp: i64, paths: &[String]) -> Self {
+        let content = paths.join("\n");
+        Self::build(timestamp, ClipboardKind::Files, &content, None, Some(paths.to_vec()), content.as_bytes())
+    }
+
+    // 이미지 본문은 설명 문자열, 크기와 해시는 PNG 바이트 기준
+    pub fn image(timestamp: i64, description: &str, png: &[u8]) -> Self {
+        Self::build(timestamp, ClipboardKind::Image, description, None, None, png)
+    }
+
+    fn build(
+        timestamp: i64,
+        kind: ClipboardKind,
+        content: &str,
+        html: Option<String>,
+        files: Option<Vec<String>>,
+        payload: &[u8],
+    ) -> Self {
+        ClipboardRecord {
+            timestamp,
+            size: payload.len() as u64,
+            kind,
+            hash: content_hash(payload),
+            pinned: false,
+            memo_id: None,
+            use_count: 0,
+            external: false,
+            compressed: false,
+            sensitive: false,
+            expires_at: None,
+            content: content.to_string(),
+            html,
+            files,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let json = self.to_json();
+        if self.compressed {
+            zstd::encode_all(json.as_slice(), COMPRESSION_LEVEL).expect("Failed to compress clipboard record")
+        } else {
+            json
+        }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            let json = zstd::decode_all(bytes).ok()?;
+            serde_json::from_slice(&json).ok()
+        } else {
+            serde_json::from_slice(bytes).ok()
+        }
+    }
+
+    // DB와 외부 저장 파일에 쓰는 값: cipher가 있으면 암호화
+    pub(super) fn encode(&self, cipher: Option<&Cipher>) -> Vec<u8> {
+        crypto::seal(cipher, self.to_bytes())
+    }
+
+    pub(super) fn decode(bytes: &[u8], cipher: Option<&Cipher>) -> Option<Self> {
+        Self::from_bytes(&crypto::open(cipher, bytes)?)
+    }
+
+    // 압축하기 전 크기 (압축률 계산용)
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Failed to serialize clipboard record")
+    }
+}
+
+/**
+ * MEMO_TABLE에 저장되는 값
+ * memo는 붙여넣을 본문이고 title, folder, description은 목록에서 찾고 정리하기 위한 선택 항목이다.
+ * folder는 "/"로 구분한 경로 (e.g., "work/sql"), None이면 최상위
+ * 암호화된 DB에서는 JSON 전체를 암호화해 저장한다 (see crypto.rs).
+ */
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct MemoRecord {
+    pub memo: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub folder: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl MemoRecord {
+    pub fn new(memo: &str) -> Self {
+        MemoRecord { memo: memo.to_string(), ..Default::default() }
+    }
+
+    pub(super) fn encode(&self, cipher: Option<&Cipher>) -> Vec<u8> {
+        crypto::seal(cipher, serde_json::to_vec(self).expect("Failed to serialize memo record"))
+    }
+
+    pub(super) fn decode(bytes: &[u8], cipher: Option<&Cipher>) -> Option<Self> {
+        // v7에서 옮긴 본문 문자열은 이전처럼 문자열 단위로 암호화되어 있음 (see migration.rs)
+        if let Some(text) = bytes.strip_prefix(V7_MEMO_MAGIC.as_slice()) {
+            let memo = crypto::open_text(cipher, std::str::from_utf8(text).ok()?)?;
+            return Some(MemoRecord::new(&memo));
+        }
+        serde_json::from_slice(&crypto::open(cipher, bytes)?).ok()
+    }
+
+    // 검색 색인에 넣는 문자열 (제목, 설명, 본문)
+    pub(super) fn search_text(&self) -> String {
+        [self.title.as_deref(), self.description.as_deref(), Some(self.memo.as_str())]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+// 폴더 경로의 각 단계 앞뒤 공백과 빈 단계를 지움, 남는 것이 없으면 None (최상위)
+pub fn normalize_folder(folder: &str) -> Option<String> {
+    let path = folder.split('/')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    (!path.is_empty()).then_some(path)
+}
+
+pub fn content_hash(payload: &[u8]) -> String {
+    Sha256::digest(payload)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
@@ -0,0 +1,115 @@
+use redb::{ReadTransaction, ReadableTable, TableDefinition, WriteTransaction};
+use serde::{Serialize, Deserialize};
+use super::local_date;
+use super::crypto::{self, Cipher, EncryptionError};
+
+/*
+ * 메모의 이전 버전
+ * format : (memo id, revision) -> serialized RevisionEntry (암호화된 DB에서는 암호화)
+ *
+ * revision은 메모마다 1부터 증가하며, 메모 내용이 바뀔 때 바뀌기 전 내용을 저장한다.
+ * 메모마다 max_memo_revisions개까지 보관하고 넘으면 오래된 버전부터 지운다.
+ * 메모가 휴지통에 있는 동안에는 그대로 두었다가 휴지통에서 비울 때 함께 지운다.
+ */
+
+const MEMO_REVISION_TABLE: TableDefinition<(u64, u64), &[u8]> = TableDefinition::new("memo_revision");
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct RevisionEntry {
+    memo: String,
+    saved_at: i64, // 이 내용으로 저장되었던 시각 (ms)
+}
+
+impl RevisionEntry {
+    fn encode(&self, cipher: Option<&Cipher>) -> Vec<u8> {
+        crypto::seal(cipher, serde_json::to_vec(self).expect("Failed to serialize memo revision"))
+    }
+
+    fn decode(bytes: &[u8], cipher: Option<&Cipher>) -> Option<Self> {
+        serde_json::from_slice(&crypto::open(cipher, bytes)?).ok()
+    }
+}
+
+// GET /memo/{id}/revisions 결과 한 줄
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct MemoRevision {
+    pub revision: u64,
+    pub memo: String,
+    pub saved_at: i64,
+    pub date: String, // 저장되었던 날짜
+}
+
+pub(super) fn open_tables(write_txn: &WriteTransaction) {
+    let _ = write_txn.open_table(MEMO_REVISION_TABLE).expect("Failed to open memo revision table");
+}
+
+// 바뀌기 전 내용을 새 버전으로 저장하고 max개를 넘는 오래된 버전을 지움, max가 0이면 저장하지 않음
+pub(super) fn push(write_txn: &WriteTransaction, cipher: Option<&Cipher>, id: u64, memo: &str, saved_at: i64, max: usize) {
+    if max == 0 {
+        return;
+    }
+    let mut table = write_txn.open_table(MEMO_REVISION_TABLE).expect("Failed to open memo revision table");
+    let revisions: Vec<u64> = table.range((id, 0)..=(id, u64::MAX))
+        .expect("Failed to iterate memo revisions")
+        .filter_map(|item| item.ok())
+        .map(|(key, _)| key.value().1)
+        .collect();
+    let next = revisions.last().map_or(1, |last| last + 1);
+    let entry = RevisionEntry { memo: memo.to_string(), saved_at };
+    table.insert((id, next), entry.encode(cipher).as_slice()).expect("Failed to insert memo revision");
+
+    let excess = (revisions.len() + 1).saturating_sub(max);
+    for revision in revisions.iter().take(excess) {
+        table.remove((id, *revision)).expect("Failed to remove memo revision");
+    }
+}
+
+// 최근 버전부터
+pub(super) fn list(read_txn: &ReadTransaction, cipher: Option<&Cipher>, id: u64) -> Vec<MemoRevision> {
+    let table = read_txn.open_table(MEMO_REVISION_TABLE).expect("Failed to open memo revision table");
+    table.range((id, 0)..=(id, u64::MAX))
+        .expect("Failed to iterate memo revisions")
+        .rev()
+        .filter_map(|item| item.ok())
+        .filter_map(|(key, value)| {
+            let entry = RevisionEntry::decode(value.value(), cipher)?;
+            Some(MemoRevision {
+                revision: key.value().1,
+                date: local_date(entry.saved_at),
+                memo: entry.memo,
+                saved_at: entry.saved_at,
+            })
+        })
+        .collect()
+}
+
+pub(super) fn get(write_txn: &WriteTransaction, cipher: Option<&Cipher>, id: u64, revision: u64) -> Option<String> {
+    let table = write_txn.open_table(MEMO_REVISION_TABLE).expect("Failed to open memo revision table");
+    let value = table.get((id, revision)).expect("Failed to read memo revision")?;
+    RevisionEntry::decode(value.value(), cipher).map(|entry| entry.memo)
+}
+
+// 메모를 완전히 삭제할 때
+pub(super) fn remove_all(write_txn: &WriteTransaction, id: u64) {
+    let mut table = write_txn.open_table(MEMO_REVISION_TABLE).expect("Failed to open memo revision table");
+    table.retain_in((id, 0)..=(id, u64::MAX), |_, _| false).expect("Failed to remove memo revisions");
+}
+
+// 모든 버전을 새 키로 다시 암호화하고 다시 쓴 개수를 반환, 읽을 수 없는 버전이 있으면 실패
+pub(super) fn rekey(write_txn: &WriteTransaction, old: Option<&Cipher>, new: Option<&Cipher>) -> Result<usize, EncryptionError> {
+    let mut table = write_txn.open_table(MEMO_REVISION_TABLE).expect("Failed to open memo revision table");
+    let entries: Vec<((u64, u64), RevisionEntry)> = table.iter()
+        .expect("Failed to iterate memo revision table")
+        .map(|item| {
+            let (key, value) = item.expect("Failed to read memo revision");
+            let (id, revision) = key.value();
+            let entry = RevisionEntry::decode(value.value(), old)
+                .ok_or_else(|| EncryptionError::Undecodable(format!("revision {} of memo {}", revision, id)))?;
+            Ok(((id, revision), entry))
+        })
+        .collect::<Result<_, EncryptionError>>()?;
+    for (key, entry) in &entries {
+        table.insert(*key, entry.encode(new).as_slice()).expect("Failed to update memo revision");
+    }
+    Ok(entries.len())
+}
@@ -0,0 +1,270 @@
+use std::collections::{BTreeMap, HashMap};
+use log::info;
+use redb::{ReadTransaction, ReadableTable, ReadableTableMetadata, TableDefinition, WriteTransaction};
+use serde::Serialize;
+use super::{ClipboardItem, ClipboardRecord, MemoItem, MemoRecord, CLIPBOARD_TABLE, MEMO_TABLE};
+use super::crypto::Cipher;
+use super::migration::METADATA_TABLE;
+use super::matcher;
+use super::tokenizer::Tokenizer;
+use regex::Regex;
+
+/*
+ * 클립보드 항목과 메모의 전문 검색 색인
+ * format : (term, id) -> 해당 문서에서 term이 나온 횟수
+ * id는 클립보드 항목과 메모가 함께 쓰는 id이므로 문서 종류와 무관하게 하나의 색인을 쓴다.
+ *
+ * term은 토크나이저가 정하며 (tokenizer.rs), 색인은 원본에서 다시 만들 수 있는 데이터이므로
+ * 스키마 마이그레이션 대신 기록된 토크나이저 id가 다르면 열 때 전체를 다시 만든다.
+ *
+ * term은 원문 조각이므로 암호화된 DB에는 색인을 저장하지 않고 (clear_index),
+ * 검색할 때마다 복호화한 문서로 같은 형식의 색인을 메모리에 만들어 쓴다.
+ */
+
+pub(super) const SEARCH_INDEX_TABLE: TableDefinition<(&str, u64), u32> = TableDefinition::new("search_index");
+const SEARCH_INDEX_VERSION_KEY: &str = "search_index_version";
+
+// 검색어 하나가 접두어로 확장될 수 있는 최대 term 개수
+const MAX_PREFIX_TERMS: usize = 64;
+// 접두어로만 일치한 term의 가중치
+const PREFIX_MATCH_WEIGHT: f64 = 0.5;
+
+// 검색 결과 하나: 점수와 원문에서 일치한 구간 [start, end) (문자 위치)
+#[derive(Serialize, Clone)]
+pub struct SearchHit<T> {
+    pub item: T,
+    pub score: f64,
+    pub highlights: Vec<[usize; 2]>,
+}
+
+// GET /search 결과: 종류별로 점수 내림차순
+#[derive(Serialize, Clone)]
+pub struct SearchResults {
+    pub clipboard: Vec<SearchHit<ClipboardItem>>,
+    pub memos: Vec<SearchHit<MemoItem>>,
+}
+
+pub(super) fn index_document(write_txn: &WriteTransaction, tokenizer: &dyn Tokenizer, id: u64, text: &str) {
+    let mut table = write_txn.open_table(SEARCH_INDEX_TABLE).expect("Failed to open search index");
+    for (term, count) in term_counts(tokenizer, text) {
+        table.insert((term.as_str(), id), count).expect("Failed to update search index");
+    }
+}
+
+pub(super) fn unindex_document(write_txn: &WriteTransaction, tokenizer: &dyn Tokenizer, id: u64, text: &str) {
+    let mut table = write_txn.open_table(SEARCH_INDEX_TABLE).expect("Failed to open search index");
+    for term in term_counts(tokenizer, text).keys() {
+        table.remove((term.as_str(), id)).expect("Failed to update search index");
+    }
+}
+
+fn term_counts(tokenizer: &dyn Tokenizer, text: &str) -> HashMap<String, u32> {
+    let mut counts = HashMap::new();
+    for token in tokenizer.tokenize(text) {
+        for term in tokenizer.index_terms(&token.term) {
+            *counts.entry(term).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+// 색인을 만든 토크나이저가 다르면 클립보드 항목과 메모로부터 다시 생성
+pub(super) fn ensure_index(write_txn: &WriteTransaction, tokenizer: &dyn Tokenizer) {
+    let version = {
+        let metadata = write_txn.open_table(METADATA_TABLE).expect("Failed to open metadata table");
+        metadata.get(SEARCH_INDEX_VERSION_KEY)
+            .expect("Failed to read search index version")
+            .map(|value| value.value())
+    };
+    if version == Some(tokenizer.id()) {
+        return;
+    }
+    rebuild_index(write_txn, tokenizer);
+}
+
+// 평문 클립보드 항목과 메모로부터 색인을 다시 생성
+pub(super) fn rebuild_index(write_txn: &WriteTransaction, tokenizer: &dyn Tokenizer) {
+    // 이전 색인은 term 형식이 다를 수 있으므로 통째로 삭제
+    write_txn.delete_table(SEARCH_INDEX_TABLE).expect("Failed to delete search index");
+    let documents = {
+        let clipboard_table = write_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+        let memo_table = write_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+        documents(&clipboard_table, &memo_table, None)
+    };
+    for (id, text) in &documents {
+        index_document(write_txn, tokenizer, *id, text);
+    }
+
+    let mut metadata = write_txn.open_table(METADATA_TABLE).expect("Failed to open metadata table");
+    metadata.insert(SEARCH_INDEX_VERSION_KEY, tokenizer.id()).expect("Failed to write search index version");
+    info!("Rebuilt search index for {} documents", documents.len());
+}
+
+// 색인을 비우고 버전을 지워 평문 DB로 돌아가면 다시 만들어지게 함
+pub(super) fn clear_index(write_txn: &WriteTransaction) {
+    write_txn.delete_table(SEARCH_INDEX_TABLE).expect("Failed to delete search index");
+    let _ = write_txn.open_table(SEARCH_INDEX_TABLE).expect("Failed to open search index");
+    let mut metadata = write_txn.open_table(METADATA_TABLE).expect("Failed to open metadata table");
+    metadata.remove(SEARCH_INDEX_VERSION_KEY).expect("Failed to remove search index version");
+}
+
+// 클립보드 항목과 메모의 (id, 검색 대상 문자열), 비밀 정보가 감지된 항목은 제외
+fn documents(
+    clipboard_table: &impl ReadableTable<u64, &'static [u8]>,
+    memo_table: &impl ReadableTable<u64, &'static [u8]>,
+    cipher: Option<&Cipher>,
+) -> Vec<(u64, String)> {
+    let clipboard = clipboard_table.iter()
+        .expect("Failed to iterate clipboard table")
+        .filter_map(|item| item.ok())
+        .filter_map(|(key, value)| Some((key.value(), ClipboardRecord::decode(value.value(), cipher)?)))
+        .filter(|(_, record)| !record.sensitive)
+        .map(|(id, record)| (id, record.content));
+    let memos = memo_table.iter()
+        .expect("Failed to iterate memo table")
+        .filter_map(|item| item.ok())
+        .filter_map(|(key, value)| Some((key.value(), MemoRecord::decode(value.value(), cipher)?.search_text())));
+    clipboard.chain(memos).collect()
+}
+
+/**
+ * 검색어의 모든 토큰을 포함하는 문서를 점수 내림차순으로 반환
+ * 각 토큰은 접두어로도 일치하며 (입력 중인 단어), 점수는 tf * idf의 합이다.
+ * 토크나이저가 검색어 토큰 하나에 여러 term을 주면 (자모, 초성 등) 문서별로 가장 높은 점수를 쓴다.
+ */
+pub(super) fn search(read_txn: &ReadTransaction, tokenizer: &dyn Tokenizer, query: &str, cipher: Option<&Cipher>) -> Vec<(u64, f64)> {
+    let query_tokens: Vec<Vec<String>> = tokenizer.tokenize(query)
+        .into_iter()
+        .map(|token| tokenizer.query_terms(&token.term))
+        .collect();
+    if query_tokens.is_empty() {
+        return Vec::new();
+    }
+
+    let table = read_txn.open_table(SEARCH_INDEX_TABLE).expect("Failed to open search index");
+    let clipboard_table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+    let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+    let total_documents = (clipboard_table.len().unwrap_or(0) + memo_table.len().unwrap_or(0)) as f64;
+    // 암호화된 DB는 저장된 색인이 없으므로 메모리에 만듦
+    let memory_index: Option<BTreeMap<(String, u64), u32>> = cipher.map(|cipher| {
+        documents(&clipboard_table, &memo_table, Some(cipher))
+            .into_iter()
+            .flat_map(|(id, text)| term_counts(tokenizer, &text)
+                .into_iter()
+                .map(move |(term, count)| ((term, id), count)))
+            .collect()
+    });
+
+    let mut scores: Option<HashMap<u64, f64>> = None;
+    for query_terms in &query_tokens {
+        let mut token_scores: HashMap<u64, f64> = HashMap::new();
+        for query_term in query_terms {
+            let postings: Box<dyn Iterator<Item = (String, u64, u32)>> = match &memory_index {
+                Some(index) => Box::new(index.range((query_term.clone(), 0)..)
+                    .map(|((term, id), count)| (term.clone(), *id, *count))),
+                None => Box::new(table.range((query_term.as_str(), 0)..)
+                    .expect("Failed to read search index")
+                    .map(|entry| {
+                        let (key, count) = entry.expect("Failed to read search index");
+                        let (term, id) = key.value();
+                        (term.to_string(), id, count.value())
+                    })),
+            };
+            for (id, score) in term_scores(postings, total_documents, query_term) {
+                let best = token_scores.entry(id).or_insert(0.0);
+                *best = best.max(score);
+            }
+        }
+
+        // 모든 토큰이 일치한 문서만 남김
+        scores = Some(match scores {
+            None => token_scores,
+            Some(scores) => scores.into_iter()
+                .filter_map(|(id, score)| Some((id, score + token_scores.get(&id)?)))
+                .collect(),
+        });
+    }
+
+    let mut results: Vec<(u64, f64)> = scores.unwrap_or_default().into_iter().collect();
+    // 점수가 같으면 최근 id가 먼저
+    results.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.0.cmp(&a.0)));
+    results
+}
+
+// 모든 문서를 훑어 fuzzy 일치한 문서를 점수 내림차순으로 반환
+pub(super) fn fuzzy_search(read_txn: &ReadTransaction, query: &str, cipher: Option<&Cipher>) -> Vec<(u64, f64)> {
+    let clipboard_table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+    let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+    let mut results: Vec<(u64, f64)> = documents(&clipboard_table, &memo_table, cipher)
+        .into_iter()
+        .filter_map(|(id, text)| Some((id, matcher::fuzzy_match(query, &text)?.0 as f64)))
+        .collect();
+    results.sort_by(|a, b| b.1.total_cmp(&a.1).then(b.0.cmp(&a.0)));
+    results
+}
+
+// 정규식과 일치하는 문서를 최근 id 순으로 반환, 점수는 일치한 횟수
+pub(super) fn regex_search(read_txn: &ReadTransaction, regex: &Regex, cipher: Option<&Cipher>) -> Vec<(u64, f64)> {
+    let clipboard_table = read_txn.open_table(CLIPBOARD_TABLE).expect("Failed to open clipboard table");
+    let memo_table = read_txn.open_table(MEMO_TABLE).expect("Failed to open memo table");
+    let mut results: Vec<(u64, f64)> = documents(&clipboard_table, &memo_table, cipher)
+        .into_iter()
+        .filter(|(_, text)| regex.is_match(text))
+        .map(|(id, text)| (id, matcher::regex_highlights(regex, &text).len() as f64))
+        .collect();
+    results.sort_by_key(|(id, _)| std::cmp::Reverse(*id));
+    results
+}
+
+// query_term으로 시작하는 색인 term들의 문서별 점수
+// entries는 (term, id) 순으로 정렬된 색인을 query_term부터 읽은 (term, id, count)
+fn term_scores(
+    entries: impl Iterator<Item = (String, u64, u32)>,
+    total_documents: f64,
+    query_term: &str,
+) -> HashMap<u64, f64> {
+    let mut term_scores: HashMap<u64, f64> = HashMap::new();
+    let mut postings: Vec<(String, u64, u32)> = Vec::new();
+    let mut terms_seen = 0;
+    let mut last_term = String::new();
+    for (term, id, count) in entries {
+        if !term.starts_with(query_term) {
+            break;
+        }
+        if term != last_term {
+            terms_seen += 1;
+            if terms_seen > MAX_PREFIX_TERMS {
+                break;
+            }
+            last_term = term.clone();
+        }
+        postings.push((term, id, count));
+    }
+
+    // term별 문서 수로 idf 계산
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for (term, _, _) in &postings {
+        *document_frequency.entry(term.as_str()).or_insert(0) += 1;
+    }
+    for (term, id, count) in &postings {
+        let idf = (1.0 + total_documents / document_frequency[term.as_str()] as f64).ln();
+        let weight = if term == query_term { 1.0 } else { PREFIX_MATCH_WEIGHT };
+        *term_scores.entry(*id).or_insert(0.0) += *count as f64 * idf * weight;
+    }
+    term_scores
+}
+
+// 원문에서 검색어 토큰과 (접두어로) 일치하는 토큰의 위치
+pub(super) fn highlights(tokenizer: &dyn Tokenizer, text: &str, query: &str) -> Vec<[usize; 2]> {
+    let query_terms: Vec<String> = tokenizer.tokenize(query)
+        .into_iter()
+        .flat_map(|token| tokenizer.query_terms(&token.term))
+        .collect();
+    tokenizer.tokenize(text)
+        .into_iter()
+        .filter(|token| tokenizer.index_terms(&token.term)
+            .iter()
+            .any(|term| query_terms.iter().any(|query_term| term.starts_with(query_term.as_str()))))
+        .map(|token| [token.start, token.end])
+        .collect()
+}
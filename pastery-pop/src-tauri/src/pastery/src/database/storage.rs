@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+use serde::{Serialize, Deserialize};
+use super::ClipboardRecord;
+use super::crypto::{self, Cipher};
+
+/*
+ * 클립보드 항목의 크기 제한
+ * - max_item_bytes를 넘는 항목은 OversizePolicy에 따라 건너뛰거나, 자르거나, DB 밖 파일로 저장한다.
+ * - max_history_bytes는 기록 전체의 크기 예산으로, 넘으면 cleanup_old_items가 오래된 항목부터 정리한다.
+ *
+ * 외부 저장 파일은 DB 파일 옆 "<db 파일>.blobs" 디렉터리에 항목 id로 저장한다.
+ * format : {id}.json -> 전체 ClipboardRecord, {id}.png -> PNG 바이트
+ * DB의 레코드에는 max_item_bytes까지 자른 본문만 남기고 external 플래그를 세운다.
+ * size는 자르기 전 크기이므로 외부 저장 항목도 max_history_bytes에 포함된다.
+ * 암호화된 DB에서는 두 파일 모두 DB의 값과 같은 키로 암호화한다.
+ */
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OversizePolicy {
+    Skip,     // 저장하지 않음
+    Truncate, // 텍스트는 앞부분만 저장 (이미지와 파일 목록은 자를 수 없으므로 건너뜀)
+    #[default]
+    External, // 전체 내용은 파일로, DB에는 앞부분만 저장
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct SizeLimits {
+    pub max_item_bytes: u64,    // 0이면 제한 없음
+    pub oversize_policy: OversizePolicy,
+    pub max_history_bytes: u64, // 0이면 제한 없음
+}
+
+impl SizeLimits {
+    pub(super) fn exceeds_item(&self, size: u64) -> bool {
+        self.max_item_bytes > 0 && size > self.max_item_bytes
+    }
+}
+
+// GET /stats/storage 결과
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct StorageStats {
+    pub item_count: u64,
+    pub pinned_count: u64,
+    pub memo_count: u64,
+    pub total_bytes: u64,        // 클립보드 항목 크기의 합 (외부 저장 포함)
+    pub external_count: u64,
+    pub external_bytes: u64,
+    pub database_bytes: u64,     // DB 파일 크기
+    pub compressed_count: u64,
+    pub compressed_bytes: u64,   // 압축된 레코드의 저장 크기
+    pub uncompressed_bytes: u64, // 압축된 레코드의 압축 전 크기
+    pub compression_ratio: f64,  // uncompressed_bytes / compressed_bytes, 압축된 레코드가 없으면 1
+    pub max_items: u64,
+    pub max_item_bytes: u64,
+    pub max_history_bytes: u64,
+}
+
+pub(super) fn blob_dir(db_path: &str) -> PathBuf {
+    PathBuf::from(format!("{}.blobs", db_path))
+}
+
+fn record_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn image_path(dir: &Path, id: u64) -> PathBuf {
+    dir.join(format!("{}.png", id))
+}
+
+// 본문을 max_bytes 이하의 문자 경계에서 자르고 HTML은 버림 (잘린 HTML은 깨지므로)
+pub(super) fn truncate(record: &mut ClipboardRecord, max_bytes: u64) {
+    let mut end = (max_bytes as usize).min(record.content.len());
+    while !record.content.is_char_boundary(end) {
+        end -= 1;
+    }
+    record.content.truncate(end);
+    record.html = None;
+}
+
+pub(super) fn has_external(dir: &Path, id: u64) -> bool {
+    record_path(dir, id).exists()
+}
+
+pub(super) fn has_external_image(dir: &Path, id: u64) -> bool {
+    image_path(dir, id).exists()
+}
+
+fn staged_path(path: PathBuf) -> PathBuf {
+    let mut staged = path.into_os_string();
+    staged.push(".tmp");
+    PathBuf::from(staged)
+}
+
+// 전체 레코드(와 PNG)를 임시 파일로 저장
+// DB 트랜잭션을 커밋한 뒤 commit_staged로 바꿔 넣고, 커밋하지 않으면 discard_staged로 버림
+pub(super) fn stage_external(
+    dir: &Path,
+    id: u64,
+    record: &ClipboardRecord,
+    png: Option<&[u8]>,
+    cipher: Option<&Cipher>,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(staged_path(record_path(dir, id)), record.encode(cipher))?;
+    if let Some(png) = png {
+        std::fs::write(staged_path(image_path(dir, id)), crypto::seal(cipher, png.to_vec()))?;
+    }
+    Ok(())
+}
+
+pub(super) fn commit_staged(dir: &Path, id: u64) -> std::io::Result<()> {
+    for path in [record_path(dir, id), image_path(dir, id)] {
+        let staged = staged_path(path.clone());
+        if staged.exists() {
+            std::fs::rename(staged, path)?;
+        }
+    }
+    Ok(())
+}
+
+// 없는 파일은 무시
+pub(super) fn discard_staged(dir: &Path, id: u64) {
+    let _ = std::fs::remove_file(staged_path(record_path(dir, id)));
+    let _ = std::fs::remove_file(staged_path(image_path(dir, id)));
+}
+
+pub(super) fn read_external(dir: &Path, id: u64, cipher: Option<&Cipher>) -> Option<ClipboardRecord> {
+    let bytes = std::fs::read(record_path(dir, id)).ok()?;
+    ClipboardRecord::decode(&bytes, cipher)
+}
+
+pub(super) fn read_external_image(dir: &Path, id: u64, cipher: Option<&Cipher>) -> Option<Vec<u8>> {
+    let bytes = std::fs::read(image_path(dir, id)).ok()?;
+    crypto::open(cipher, &bytes).map(|png| png.into_owned())
+}
+
+// 없는 파일은 무시
+pub(super) fn remove_external(dir: &Path, id: u64) {
+    let _ = std::fs::remove_file(record_path(dir, id));
+    let _ = std::fs::remove_file(image_path(dir, id));
+}
@@ -0,0 +1,168 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+/*
+ * 검색 색인용 토크나이저
+ * 원문을 토큰으로 나누고, 토큰 하나를 색인에 넣을 term들과 검색어 토큰 하나로 찾을 term들을 정한다.
+ * 검색은 query_terms의 각 term을 색인 term의 접두어로 비교한다.
+ *
+ * id()는 색인 형식의 식별자로 metadata에 기록되며, 값이 바뀌면 색인을 다시 만든다.
+ */
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Token {
+    pub term: String,
+    pub start: usize, // 원문에서의 문자(char) 위치
+    pub end: usize,
+}
+
+pub trait Tokenizer: Send + Sync {
+    fn id(&self) -> u64;
+
+    // 문자/숫자가 아닌 문자를 기준으로 나누고 NFC 정규화 후 소문자로 변환
+    // 위치는 정규화 전 원문 기준이므로 NFD 원문에서도 하이라이트 위치가 맞는다.
+    fn tokenize(&self, text: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut current = String::new();
+        let mut start = 0;
+        let mut length = 0;
+        for (position, ch) in text.chars().enumerate() {
+            length = position + 1;
+            // 결합 문자(NFD의 악센트 등)는 앞 글자에 붙은 것으로 취급
+            if ch.is_alphanumeric() || (!current.is_empty() && is_combining_mark(ch)) {
+                if current.is_empty() {
+                    start = position;
+                }
+                current.push(ch);
+            } else if !current.is_empty() {
+                tokens.push(Token { term: normalize(&std::mem::take(&mut current)), start, end: position });
+            }
+        }
+        if !current.is_empty() {
+            tokens.push(Token { term: normalize(&current), start, end: length });
+        }
+        tokens
+    }
+
+    fn index_terms(&self, term: &str) -> Vec<String> {
+        vec![term.to_string()]
+    }
+
+    fn query_terms(&self, term: &str) -> Vec<String> {
+        vec![term.to_string()]
+    }
+}
+
+fn normalize(token: &str) -> String {
+    token.nfc().flat_map(char::to_lowercase).collect()
+}
+
+// 토큰을 그대로 색인
+#[allow(dead_code)]
+pub struct SimpleTokenizer;
+
+impl Tokenizer for SimpleTokenizer {
+    fn id(&self) -> u64 {
+        1
+    }
+}
+
+/*
+ * 한글 토큰은 원형 외에 자모 분해형과 초성형도 색인한다.
+ * - 자모: "클립" -> "ㅋㅡㄹㄹㅣㅂ" (겹받침/겹모음도 입력 순서대로 분해) 조합 중인 "클ㄹ"로도 찾을 수 있음
+ * - 초성: "클립보드" -> "ㅋㄹㅂㄷ" 초성만 입력한 검색어로 찾을 수 있음
+ * 분해형은 앞에 구분 문자를 붙여 원형 term과 섞이지 않게 한다.
+ */
+pub struct HangulTokenizer;
+
+const JAMO_MARK: char = '\u{1}';
+const CHOSEONG_MARK: char = '\u{2}';
+
+impl Tokenizer for HangulTokenizer {
+    fn id(&self) -> u64 {
+        2
+    }
+
+    fn index_terms(&self, term: &str) -> Vec<String> {
+        let mut terms = vec![term.to_string()];
+        if term.chars().any(is_hangul_syllable) {
+            terms.push(format!("{}{}", JAMO_MARK, decompose_jamo(term)));
+            terms.push(format!("{}{}", CHOSEONG_MARK, choseong(term)));
+        }
+        terms
+    }
+
+    fn query_terms(&self, term: &str) -> Vec<String> {
+        let mut terms = vec![term.to_string()];
+        if !term.is_empty() && term.chars().all(is_compatibility_consonant) {
+            terms.push(format!("{}{}", CHOSEONG_MARK, term));
+        } else if term.chars().any(|ch| is_hangul_syllable(ch) || is_compatibility_jamo(ch)) {
+            terms.push(format!("{}{}", JAMO_MARK, decompose_jamo(term)));
+        }
+        terms
+    }
+}
+
+const SYLLABLE_BASE: u32 = 0xAC00;
+const SYLLABLE_LAST: u32 = 0xD7A3;
+const CHOSEONG: [&str; 19] = [
+    "ㄱ", "ㄲ", "ㄴ", "ㄷ", "ㄸ", "ㄹ", "ㅁ", "ㅂ", "ㅃ", "ㅅ", "ㅆ", "ㅇ", "ㅈ", "ㅉ", "ㅊ", "ㅋ", "ㅌ", "ㅍ", "ㅎ",
+];
+// 겹모음은 입력 순서대로 분해 (ㅘ = ㅗ + ㅏ)
+const JUNGSEONG: [&str; 21] = [
+    "ㅏ", "ㅐ", "ㅑ", "ㅒ", "ㅓ", "ㅔ", "ㅕ", "ㅖ", "ㅗ", "ㅗㅏ", "ㅗㅐ", "ㅗㅣ", "ㅛ", "ㅜ", "ㅜㅓ", "ㅜㅔ", "ㅜㅣ", "ㅠ", "ㅡ", "ㅡㅣ", "ㅣ",
+];
+// 겹받침도 입력 순서대로 분해 (ㄳ = ㄱ + ㅅ)
+const JONGSEONG: [&str; 28] = [
+    "", "ㄱ", "ㄲ", "ㄱㅅ", "ㄴ", "ㄴㅈ", "ㄴㅎ", "ㄷ", "ㄹ", "ㄹㄱ", "ㄹㅁ", "ㄹㅂ", "ㄹㅅ", "ㄹㅌ", "ㄹㅍ", "ㄹㅎ",
+    "ㅁ", "ㅂ", "ㅂㅅ", "ㅅ", "ㅆ", "ㅇ", "ㅈ", "ㅊ", "ㅋ", "ㅌ", "ㅍ", "ㅎ",
+];
+// 호환 자모 중 단독으로 입력된 겹자모 (ㄳ, ㅘ 등)
+const COMPOUND_JAMO: [(char, &str); 18] = [
+    ('ㄳ', "ㄱㅅ"), ('ㄵ', "ㄴㅈ"), ('ㄶ', "ㄴㅎ"), ('ㄺ', "ㄹㄱ"), ('ㄻ', "ㄹㅁ"), ('ㄼ', "ㄹㅂ"), ('ㄽ', "ㄹㅅ"), ('ㄾ', "ㄹㅌ"),
+    ('ㄿ', "ㄹㅍ"), ('ㅀ', "ㄹㅎ"), ('ㅄ', "ㅂㅅ"), ('ㅘ', "ㅗㅏ"), ('ㅙ', "ㅗㅐ"), ('ㅚ', "ㅗㅣ"), ('ㅝ', "ㅜㅓ"), ('ㅞ', "ㅜㅔ"),
+    ('ㅟ', "ㅜㅣ"), ('ㅢ', "ㅡㅣ"),
+];
+
+fn is_hangul_syllable(ch: char) -> bool {
+    (SYLLABLE_BASE..=SYLLABLE_LAST).contains(&(ch as u32))
+}
+
+fn is_compatibility_jamo(ch: char) -> bool {
+    ('ㄱ'..='ㅣ').contains(&ch)
+}
+
+fn is_compatibility_consonant(ch: char) -> bool {
+    ('ㄱ'..='ㅎ').contains(&ch)
+}
+
+// 한글 음절과 겹자모를 입력 순서의 호환 자모로 분해, 그 외 문자는 그대로
+pub fn decompose_jamo(text: &str) -> String {
+    let mut jamo = String::new();
+    for ch in text.chars() {
+        if is_hangul_syllable(ch) {
+            let index = ch as u32 - SYLLABLE_BASE;
+            jamo.push_str(CHOSEONG[(index / 588) as usize]);
+            jamo.push_str(JUNGSEONG[(index % 588 / 28) as usize]);
+            jamo.push_str(JONGSEONG[(index % 28) as usize]);
+        } else if let Some((_, parts)) = COMPOUND_JAMO.iter().find(|(compound, _)| *compound == ch) {
+            jamo.push_str(parts);
+        } else {
+            jamo.push(ch);
+        }
+    }
+    jamo
+}
+
+// 한글 음절의 초성만 남김, 그 외 문자는 그대로
+pub fn choseong(text: &str) -> String {
+    text.chars()
+        .map(|ch| {
+            if is_hangul_syllable(ch) {
+                CHOSEONG[((ch as u32 - SYLLABLE_BASE) / 588) as usize].to_string()
+            } else {
+                ch.to_string()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,171 @@
+use std::ops::Bound;
+use redb::{ReadTransaction, ReadableTable, TableDefinition, WriteTransaction};
+use serde::{Serialize, Deserialize};
+use super::{local_date, ClipboardKind, ClipboardRecord, MemoMeta, MemoRecord};
+use super::crypto::{self, Cipher, EncryptionError};
+
+/*
+ * 사용자가 삭제한 클립보드 항목과 메모를 보관하는 휴지통
+ * format : id -> serialized TrashEntry (원래 id 그대로, 암호화된 DB에서는 암호화)
+ * format : (deleted_at, id) -> () 삭제 시각 순 색인 (오래된 항목을 비울 때 사용)
+ *
+ * 휴지통에는 DELETE 요청으로 지운 항목만 옮긴다.
+ * max_items/max_history_bytes 정리, 보관 기간 정리, 만료된 비밀 정보는 휴지통을 거치지 않고 바로 삭제한다.
+ * 클립보드 항목의 이미지(IMAGE_TABLE)와 외부 저장 파일은 그대로 두었다가 휴지통에서 비울 때 삭제한다.
+ * 휴지통의 항목은 목록, 검색, 태그와 컬렉션에서 빠지며 복원하면 같은 id로 돌아간다.
+ */
+
+const TRASH_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("trash");
+const TRASH_INDEX_TABLE: TableDefinition<(i64, u64), ()> = TableDefinition::new("trash_by_time");
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(super) struct TrashEntry {
+    pub deleted_at: i64, // 삭제 시각 (ms)
+    pub tags: Vec<String>,
+    pub collections: Vec<String>,
+    pub item: TrashedItem,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub(super) enum TrashedItem {
+    Clipboard { record: ClipboardRecord },
+    Memo {
+        #[serde(flatten)]
+        record: MemoRecord,     // 본문은 "memo" 그대로 두어 이전에 휴지통으로 옮긴 메모도 읽힘
+        item_id: Option<u64>,   // 붙어 있던 클립보드 항목
+        #[serde(default)]
+        meta: Option<MemoMeta>, // 만든/수정한 시각과 순서
+    },
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum TrashItemType {
+    Clipboard,
+    Memo,
+}
+
+// GET /trash 결과 한 줄
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct TrashItem {
+    pub id: u64,
+    pub item_type: TrashItemType,
+    pub kind: Option<ClipboardKind>, // 클립보드 항목의 종류
+    pub deleted_at: i64,
+    pub date: String,                // 삭제한 날짜
+    pub content: String,             // 클립보드 항목의 본문 또는 메모
+    pub sensitive: bool,
+    pub tags: Vec<String>,
+    pub collections: Vec<String>,
+}
+
+impl TrashItem {
+    fn from_entry(id: u64, entry: TrashEntry) -> Self {
+        let (item_type, kind, content, sensitive) = match entry.item {
+            TrashedItem::Clipboard { record } => (TrashItemType::Clipboard, Some(record.kind), record.content, record.sensitive),
+            TrashedItem::Memo { record, .. } => (TrashItemType::Memo, None, record.memo, false),
+        };
+        TrashItem {
+            id,
+            item_type,
+            kind,
+            deleted_at: entry.deleted_at,
+            date: local_date(entry.deleted_at),
+            content,
+            sensitive,
+            tags: entry.tags,
+            collections: entry.collections,
+        }
+    }
+}
+
+impl TrashEntry {
+    fn encode(&self, cipher: Option<&Cipher>) -> Vec<u8> {
+        crypto::seal(cipher, serde_json::to_vec(self).expect("Failed to serialize trash entry"))
+    }
+
+    fn decode(bytes: &[u8], cipher: Option<&Cipher>) -> Option<Self> {
+        serde_json::from_slice(&crypto::open(cipher, bytes)?).ok()
+    }
+}
+
+pub(super) fn open_tables(write_txn: &WriteTransaction) {
+    let _ = write_txn.open_table(TRASH_TABLE).expect("Failed to open trash table");
+    let _ = write_txn.open_table(TRASH_INDEX_TABLE).expect("Failed to open trash index table");
+}
+
+pub(super) fn put(write_txn: &WriteTransaction, cipher: Option<&Cipher>, id: u64, entry: &TrashEntry) {
+    let mut table = write_txn.open_table(TRASH_TABLE).expect("Failed to open trash table");
+    table.insert(id, entry.encode(cipher).as_slice()).expect("Failed to insert trash entry");
+    let mut index = write_txn.open_table(TRASH_INDEX_TABLE).expect("Failed to open trash index table");
+    index.insert((entry.deleted_at, id), ()).expect("Failed to update trash index");
+}
+
+// 휴지통에서 꺼냄 (복원하거나 완전히 삭제할 때), 없으면 None
+pub(super) fn take(write_txn: &WriteTransaction, cipher: Option<&Cipher>, id: u64) -> Option<TrashEntry> {
+    let mut table = write_txn.open_table(TRASH_TABLE).expect("Failed to open trash table");
+    let entry = table.remove(id).expect("Failed to remove trash entry")
+        .and_then(|value| TrashEntry::decode(value.value(), cipher))?;
+    let mut index = write_txn.open_table(TRASH_INDEX_TABLE).expect("Failed to open trash index table");
+    index.remove((entry.deleted_at, id)).expect("Failed to update trash index");
+    Some(entry)
+}
+
+// 휴지통에 있는 항목을 고쳐 다시 저장 (먼저 복원한 쪽이 메모 연결을 넘겨줄 때), 없으면 false
+pub(super) fn update(write_txn: &WriteTransaction, cipher: Option<&Cipher>, id: u64, f: impl FnOnce(&mut TrashEntry)) -> bool {
+    let mut table = write_txn.open_table(TRASH_TABLE).expect("Failed to open trash table");
+    let entry = table.get(id).expect("Failed to read trash entry")
+        .and_then(|value| TrashEntry::decode(value.value(), cipher));
+    let Some(mut entry) = entry else {
+        return false;
+    };
+    f(&mut entry);
+    table.insert(id, entry.encode(cipher).as_slice()).expect("Failed to update trash entry");
+    true
+}
+
+// 최근에 삭제한 항목부터
+pub(super) fn list(read_txn: &ReadTransaction, cipher: Option<&Cipher>) -> Vec<TrashItem> {
+    let table = read_txn.open_table(TRASH_TABLE).expect("Failed to open trash table");
+    let index = read_txn.open_table(TRASH_INDEX_TABLE).expect("Failed to open trash index table");
+    index.iter()
+        .expect("Failed to iterate trash index")
+        .rev()
+        .filter_map(|item| item.ok())
+        .filter_map(|(key, _)| {
+            let id = key.value().1;
+            let value = table.get(id).ok()??;
+            Some(TrashItem::from_entry(id, TrashEntry::decode(value.value(), cipher)?))
+        })
+        .collect()
+}
+
+// cutoff(ms)보다 먼저 삭제한 항목의 id, cutoff가 None이면 전부
+pub(super) fn ids_before(write_txn: &WriteTransaction, cutoff: Option<i64>) -> Vec<u64> {
+    let index = write_txn.open_table(TRASH_INDEX_TABLE).expect("Failed to open trash index table");
+    let end = cutoff.map_or(Bound::Unbounded, |cutoff| Bound::Excluded((cutoff, 0)));
+    index.range::<(i64, u64)>((Bound::Unbounded, end))
+        .expect("Failed to iterate trash index")
+        .filter_map(|item| item.ok())
+        .map(|(key, _)| key.value().1)
+        .collect()
+}
+
+// 모든 항목을 새 키로 다시 암호화하고 다시 쓴 항목을 반환 (외부 저장 파일은 호출한 쪽에서 처리), 읽을 수 없는 항목이 있으면 실패
+pub(super) fn rekey(write_txn: &WriteTransaction, old: Option<&Cipher>, new: Option<&Cipher>) -> Result<Vec<(u64, TrashEntry)>, EncryptionError> {
+    let mut table = write_txn.open_table(TRASH_TABLE).expect("Failed to open trash table");
+    let entries: Vec<(u64, TrashEntry)> = table.iter()
+        .expect("Failed to iterate trash table")
+        .map(|item| {
+            let (key, value) = item.expect("Failed to read trash entry");
+            let entry = TrashEntry::decode(value.value(), old)
+                .ok_or_else(|| EncryptionError::Undecodable(format!("trash entry {}", key.value())))?;
+            Ok((key.value(), entry))
+        })
+        .collect::<Result<_, EncryptionError>>()?;
+    for (id, entry) in &entries {
+        table.insert(*id, entry.encode(new).as_slice()).expect("Failed to update trash entry");
+    }
+    Ok(entries)
+}
@@ -0,0 +1,39 @@
+use std::borrow::Cow;
+use std::io::Cursor;
+use arboard::ImageData;
+use image::{DynamicImage, ImageFormat, RgbaImage};
+
+// arboard의 RGBA 이미지를 PNG 바이트로 변환
+pub fn encode_png(image: &ImageData) -> Result<Vec<u8>, image::ImageError> {
+    let buffer = RgbaImage::from_raw(image.width as u32, image.height as u32, image.bytes.to_vec())
+        .ok_or_else(|| image::ImageError::Parameter(image::error::ParameterError::from_kind(
+            image::error::ParameterErrorKind::DimensionMismatch,
+        )))?;
+
+    let mut png = Cursor::new(Vec::new());
+    buffer.write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+// 저장된 PNG 바이트를 시스템 클립보드에 다시 올릴 수 있는 형태로 변환
+pub fn decode_png(png: &[u8]) -> Result<ImageData<'static>, image::ImageError> {
+    let rgba = image::load_from_memory_with_format(png, ImageFormat::Png)?.to_rgba8();
+    Ok(ImageData {
+        width: rgba.width() as usize,
+        height: rgba.height() as usize,
+        bytes: Cow::Owned(rgba.into_raw()),
+    })
+}
+
+// 팝업 표시용 썸네일 생성 (비율 유지, 가장 긴 변이 max_size 이하)
+pub fn thumbnail_png(png: &[u8], max_size: u32) -> Result<Vec<u8>, image::ImageError> {
+    let image = image::load_from_memory_with_format(png, ImageFormat::Png)?;
+    if image.width() <= max_size && image.height() <= max_size {
+        return Ok(png.to_vec());
+    }
+
+    let thumbnail: DynamicImage = image.thumbnail(max_size, max_size);
+    let mut out = Cursor::new(Vec::new());
+    thumbnail.write_to(&mut out, ImageFormat::Png)?;
+    Ok(out.into_inner())
+}
@@ -3,7 +3,10 @@
 // it communicates with the GUI program via a message channel
 // it uses a database to store clipboard history
 mod database;
+mod image_data;
 mod key_combination;
+mod maintenance;
+mod secrets;
 mod server;
 mod settings;
 
@@ -11,7 +14,7 @@ use std::sync::{mpsc, Arc, Mutex};
 use arboard::Clipboard;
 use rdev::{listen, Event};
 use settings::Settings;
-use log::{info, error};
+use log::{info, warn, error};
 
 fn create_key_combination_from_settings(binding: &settings::KeyBinding) -> key_combination::KeyCombination {
     let keys = settings::key_binding_to_keys(binding);
@@ -28,6 +31,7 @@ fn key_event_handle(
     clipboard_data: Arc<Mutex<database::ClipboardData>>,
     settings: Settings,
 ) {
+    let secret_scanner = secrets::SecretScanner::from_settings(&settings);
     let mut copy_key_combination = create_key_combination_from_settings(&settings.copy_key);
     let mut paste_key_combination = create_key_combination_from_settings(&settings.paste_key);
     
@@ -42,10 +46,39 @@ fn key_event_handle(
                         if copy_key_combination.contains(key) {
                             if copy_key_combination.is_active() {
                                 let mut clipboard = Clipboard::new().unwrap();
-                                if let Ok(text) = clipboard.get_text() {
-                                    info!("Clipboard content: {}", text);
+                                // 파일 관리자에서 복사한 경우 텍스트보다 파일 목록을 우선
+                                let files = clipboard.get().file_list().ok().filter(|files| !files.is_empty());
+                                if let Some(files) = files {
+                                    let paths: Vec<String> = files.iter()
+                                        .map(|path| path.to_string_lossy().to_string())
+                                        .collect();
+                                    info!("Clipboard files: {:?}", paths);
                                     let clipboard_data = clipboard_data.lock().unwrap();
-                                    clipboard_data.write(&text);
+                                    clipboard_data.write_files(&paths);
+                                } else if let Ok(text) = clipboard.get_text() {
+                                    // 비밀 정보가 감지되면 내용을 로그에 남기지 않고 설정된 action을 적용
+                                    match secret_scanner.scan(&text) {
+                                        Some(detection) => write_secret(&clipboard_data, &secret_scanner, &text, &detection),
+                                        None => {
+                                            info!("Clipboard content: {}", text);
+                                            // 서식 있는 텍스트는 HTML과 plain text를 함께 저장
+                                            let html = clipboard.get().html().ok().filter(|html| !html.trim().is_empty());
+                                            let clipboard_data = clipboard_data.lock().unwrap();
+                                            match html {
+                                                Some(html) => clipboard_data.write_html(&text, &html),
+                                                None => clipboard_data.write(&text),
+                                            }
+                                        }
+                                    }
+                                } else if let Ok(image) = clipboard.get_image() {
+                                    match image_data::encode_png(&image) {
+                                        Ok(png) => {
+                                            info!("Clipboard image: {}x{} ({} bytes)", image.width, image.height, png.len());
+                                            let clipboard_data = clipboard_data.lock().unwrap();
+                                            clipboard_data.write_image(&png, image.width, image.height);
+                                        }
+                                        Err(e) => error!("Failed to encode clipboard image: {}", e),
+                                    }
                                 }
                             }
                             copy_key_combination.release_key(key);
@@ -64,7 +97,7 @@ fn key_event_handle(
                         //     let clipboard_data = clipboard_data.lock().unwrap();
                         //     let items = clipboard_data.get_clipboard_items(Some(5));
                         //     for item in items {
-                        //         info!("Clipboard data: {}-{}: \"{}\"", item.date, item.sequence, item.content);
+                        //         info!("Clipboard data: {} ({}): \"{}\"", item.id, item.date, item.content);
                         //     }
                         // }
                     },
@@ -76,6 +109,46 @@ fn key_event_handle(
     }
 }
 
+// 설정된 키로 잠금 해제하고, 암호화가 켜졌는데 아직 평문인 DB는 암호화
+// 키가 없으면 잠긴 채로 시작하며 POST /unlock으로 해제한다.
+fn open_encryption(clipboard_data: &mut database::ClipboardData, settings: &Settings) {
+    if !settings.encryption {
+        if clipboard_data.is_encrypted() {
+            info!("Clipboard database is encrypted; unlock with POST /unlock");
+        }
+        return;
+    }
+    match settings.encryption_key() {
+        Some(key) if clipboard_data.is_encrypted() => {
+            if let Err(e) = clipboard_data.unlock(&key) {
+                error!("Failed to unlock clipboard database: {}", e);
+            }
+        }
+        Some(key) => match clipboard_data.rekey(Some(&key)) {
+            Ok(count) => info!("Encrypted {} stored values in clipboard database", count),
+            Err(e) => error!("Failed to encrypt clipboard database: {}", e),
+        },
+        None if clipboard_data.is_encrypted() => info!("Clipboard database is encrypted; unlock with POST /unlock"),
+        None => warn!("Encryption is enabled but no key file or passphrase is set; encrypt with POST /encryption/rekey"),
+    }
+}
+
+// 비밀 정보가 감지된 텍스트는 HTML 없이 sensitive 항목으로 저장
+fn write_secret(
+    clipboard_data: &Mutex<database::ClipboardData>,
+    secret_scanner: &secrets::SecretScanner,
+    text: &str,
+    detection: &secrets::Detection,
+) {
+    info!("Clipboard content matched secret detectors {:?}: {:?}", detection.detectors(), detection.action);
+    let clipboard_data = clipboard_data.lock().unwrap();
+    match detection.action {
+        secrets::SecretAction::Skip => {}
+        secrets::SecretAction::Mask => clipboard_data.write_secret(&detection.mask(text), None),
+        secrets::SecretAction::Expire => clipboard_data.write_secret(text, Some(secret_scanner.expires_at())),
+    }
+}
+
 fn callback(event: Event, channel: mpsc::Sender<Event>) {
     channel.send(event.clone()).unwrap();
 }
@@ -86,14 +159,26 @@ pub fn run_pastery() {
     
     // 설정 로드
     let settings = Settings::load();
-    info!("Settings loaded. Server will run on port {}, max clipboard items: {}", 
-             settings.server_port, settings.max_clipboard_items);
+    info!("Settings loaded. Server will run on port {}, max clipboard items: {}, dedup mode: {:?}", 
+             settings.server_port, settings.max_clipboard_items, settings.dedup_mode);
+    info!("Max item bytes: {} ({:?}), max history bytes: {}, compression threshold: {}",
+             settings.max_item_bytes, settings.oversize_policy, settings.max_history_bytes, settings.compression_threshold);
     
     let db_path = settings.db_path.clone();
-    let clipboard_data = Arc::new(Mutex::new(database::ClipboardData::new(
+    let mut clipboard_data = database::ClipboardData::new(
         db_path.clone(),
         settings.max_clipboard_items
-    )));
+    );
+    clipboard_data.set_dedup_mode(settings.dedup_mode);
+    clipboard_data.set_size_limits(database::SizeLimits {
+        max_item_bytes: settings.max_item_bytes,
+        oversize_policy: settings.oversize_policy,
+        max_history_bytes: settings.max_history_bytes,
+    });
+    clipboard_data.set_compression_threshold(settings.compression_threshold);
+    clipboard_data.set_max_memo_revisions(settings.max_memo_revisions);
+    open_encryption(&mut clipboard_data, &settings);
+    let clipboard_data = Arc::new(Mutex::new(clipboard_data));
     
     // 키보드 이벤트 처리를 위한 채널
     let (tx, rx) = mpsc::channel();
@@ -101,12 +186,19 @@ pub fn run_pastery() {
     // 서버용 클립보드 데이터 복사
     let server_clipboard_data = clipboard_data.clone();
     let server_port = settings.server_port;
+    let retention = maintenance::RetentionPolicy::from_settings(&settings);
+    let key_file = settings.encryption_key_file.clone();
     
     // 서버 시작 (백그라운드)
     tokio::spawn(async move {
-        server::start_server(server_clipboard_data, server_port).await;
+        server::start_server(server_clipboard_data, server_port, retention, key_file).await;
     });
     
+    // 보관 기간 정리 (백그라운드)
+    tokio::spawn(maintenance::run(clipboard_data.clone(), retention));
+    tokio::spawn(maintenance::run_expiry(clipboard_data.clone()));
+    tokio::spawn(maintenance::run_trash(clipboard_data.clone(), settings.trash_retention_days));
+    
     // 키보드 이벤트 처리 스레드
     let keyboard_clipboard_data = clipboard_data.clone();
     let keyboard_settings = settings.clone();
@@ -0,0 +1,133 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::{DateTime, Days, Local, NaiveDate};
+use log::info;
+use serde::Serialize;
+use crate::database::ClipboardData;
+use crate::settings::Settings;
+
+/*
+ * 시간 기준 클립보드 기록 정리 (백그라운드 작업과 POST /maintenance/prune에서 사용)
+ * - retention_days: 복사한 지 N일이 지난 항목 삭제
+ * - midnight_clear_days: 매일 자정에 X일 전 자정보다 먼저 복사된 항목 삭제 (0이면 오늘 이전 전부)
+ * 두 기준 중 더 늦은 시각을 cutoff로 쓰며, 고정된 항목은 삭제하지 않는다.
+ * 자정 기준의 cutoff는 하루 동안 바뀌지 않으므로 언제 실행해도 같은 결과가 된다.
+ * 이렇게 정리한 항목은 휴지통을 거치지 않으며, 휴지통은 trash_retention_days가 지난 항목을 따로 비운다.
+ */
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// 만료 시각이 있는 항목을 확인하는 최대 간격 (새로 저장된 항목의 만료를 놓치지 않도록)
+const EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const MILLIS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+// POST /maintenance/prune?days=N 에서 받는 최대 일수 (100년)
+pub const MAX_PRUNE_DAYS: u64 = 36500;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RetentionPolicy {
+    pub retention_days: u64,             // 0이면 기간 제한 없음
+    pub midnight_clear_days: Option<u64>, // None이면 자정 정리 안 함
+}
+
+#[derive(Serialize)]
+pub struct PruneResult {
+    pub removed: usize,
+    pub cutoff: i64, // 이 시각(ms)보다 먼저 복사된 항목을 삭제함
+}
+
+impl RetentionPolicy {
+    pub fn from_settings(settings: &Settings) -> Self {
+        RetentionPolicy {
+            retention_days: settings.retention_days,
+            midnight_clear_days: settings.midnight_clear_days,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.retention_days > 0 || self.midnight_clear_days.is_some()
+    }
+
+    // now 기준 삭제 기준 시각(ms), 정리 기준이 없으면 None
+    pub fn cutoff(&self, now: DateTime<Local>) -> Option<i64> {
+        let retention = (self.retention_days > 0)
+            .then(|| cutoff_days_ago(now, self.retention_days))
+            .flatten();
+        let midnight = self.midnight_clear_days
+            .and_then(|days| now.date_naive().checked_sub_days(Days::new(days)))
+            .and_then(local_midnight)
+            .map(|midnight| midnight.timestamp_millis());
+        retention.max(midnight)
+    }
+}
+
+// N일보다 오래된 항목의 삭제 기준 시각(ms), days가 너무 커서 계산할 수 없으면 None
+pub fn cutoff_days_ago(now: DateTime<Local>, days: u64) -> Option<i64> {
+    let span = i64::try_from(days).ok()?.checked_mul(MILLIS_PER_DAY)?;
+    now.timestamp_millis().checked_sub(span)
+}
+
+pub fn prune(clipboard_data: &Mutex<ClipboardData>, cutoff: i64) -> PruneResult {
+    let removed = clipboard_data.lock().unwrap().prune_before(cutoff);
+    if removed > 0 {
+        info!("Pruned {} clipboard items copied before {}", removed, cutoff);
+    }
+    PruneResult { removed, cutoff }
+}
+
+// 시작할 때, 그리고 PRUNE_INTERVAL과 자정 중 먼저 오는 시각마다 정리
+pub async fn run(clipboard_data: Arc<Mutex<ClipboardData>>, policy: RetentionPolicy) {
+    if !policy.is_enabled() {
+        return;
+    }
+    info!("Retention: {} days, midnight clear: {:?}", policy.retention_days, policy.midnight_clear_days);
+    loop {
+        let now = Local::now();
+        if let Some(cutoff) = policy.cutoff(now) {
+            prune(&clipboard_data, cutoff);
+        }
+        // 자정을 확실히 넘긴 뒤에 깨어나도록 1초 여유
+        let until_midnight = now.date_naive().succ_opt()
+            .and_then(local_midnight)
+            .and_then(|midnight| (midnight - now).to_std().ok())
+            .map_or(PRUNE_INTERVAL, |duration| duration + Duration::from_secs(1));
+        tokio::time::sleep(PRUNE_INTERVAL.min(until_midnight)).await;
+    }
+}
+
+// 짧은 만료로 저장된 비밀 정보 항목을 만료 시각에 삭제
+pub async fn run_expiry(clipboard_data: Arc<Mutex<ClipboardData>>) {
+    loop {
+        let now = Local::now().timestamp_millis();
+        let next_expiry = {
+            let clipboard_data = clipboard_data.lock().unwrap();
+            let removed = clipboard_data.prune_expired(now);
+            if removed > 0 {
+                info!("Removed {} expired clipboard items", removed);
+            }
+            clipboard_data.next_expiry()
+        };
+        let until_expiry = next_expiry
+            .map_or(EXPIRY_CHECK_INTERVAL, |expires_at| Duration::from_millis(expires_at.saturating_sub(now).max(0) as u64));
+        tokio::time::sleep(EXPIRY_CHECK_INTERVAL.min(until_expiry)).await;
+    }
+}
+
+// 휴지통에서 days일이 지난 항목을 PRUNE_INTERVAL마다 완전히 삭제
+pub async fn run_trash(clipboard_data: Arc<Mutex<ClipboardData>>, days: u64) {
+    if days == 0 {
+        return;
+    }
+    info!("Trash retention: {} days", days);
+    loop {
+        if let Some(cutoff) = cutoff_days_ago(Local::now(), days) {
+            let purged = clipboard_data.lock().unwrap().purge_trash(Some(cutoff));
+            if purged > 0 {
+                info!("Purged {} items deleted before {} from trash", purged, cutoff);
+            }
+        }
+        tokio::time::sleep(PRUNE_INTERVAL).await;
+    }
+}
+
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Local>> {
+    date.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest()
+}
@@ -0,0 +1,247 @@
+use std::ops::Range;
+use chrono::Local;
+use log::warn;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use crate::settings::Settings;
+
+/*
+ * 클립보드 텍스트를 저장하기 전에 비밀 정보를 찾는 detector pipeline (key_event_handle에서 사용)
+ * 모든 detector를 실행해 일치한 구간을 모으고, 일치한 detector들의 action 중 가장 강한 것을 적용한다.
+ * - skip: 저장하지 않음
+ * - mask: 일치한 구간을 "[REDACTED name]"으로 바꿔 저장
+ * - expire: 그대로 저장하되 secret_expiry_minutes가 지나면 삭제 (고정하면 유지)
+ * 저장된 항목에는 sensitive 플래그가 붙어 popup이 내용을 가린다.
+ *
+ * 기본 detector (disabled_secret_detectors로 끌 수 있음)
+ * - aws_access_key, aws_secret_key, jwt, private_key, credit_card (Luhn 검사)
+ * - password: 공백 없는 한 줄 전체가 대소문자, 숫자, 특수문자를 모두 포함 (비밀번호 관리자에서 복사한 값)
+ * 사용자 detector는 pastery.json의 secret_patterns에 이름과 정규식, action(생략하면 secret_action)으로 추가한다.
+ */
+
+const MILLIS_PER_MINUTE: i64 = 60 * 1000;
+const PASSWORD_LENGTH: Range<usize> = 12..65;
+
+// 강한 순서: skip > mask > expire
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretAction {
+    Expire, // 짧은 만료 시간으로 저장
+    #[default]
+    Mask,   // 일치한 부분을 가려서 저장
+    Skip,   // 저장하지 않음
+}
+
+// pastery.json의 사용자 정의 detector
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SecretPattern {
+    pub name: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub action: Option<SecretAction>,
+}
+
+// 본문에서 비밀 정보 구간을 찾는 detector, SecretScanner::with_detector로 추가할 수 있다.
+pub trait Detector: Send {
+    fn name(&self) -> &str;
+    fn action(&self) -> SecretAction;
+    fn find(&self, text: &str) -> Vec<Range<usize>>; // 바이트 구간
+}
+
+// 정규식 detector, validate가 있으면 일치한 문자열을 한 번 더 검사
+struct PatternDetector {
+    name: String,
+    regex: Regex,
+    action: SecretAction,
+    validate: Option<fn(&str) -> bool>,
+}
+
+impl Detector for PatternDetector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn action(&self) -> SecretAction {
+        self.action
+    }
+
+    fn find(&self, text: &str) -> Vec<Range<usize>> {
+        self.regex.find_iter(text)
+            .filter(|found| self.validate.is_none_or(|validate| validate(found.as_str())))
+            .map(|found| found.range())
+            .collect()
+    }
+}
+
+struct PasswordDetector {
+    action: SecretAction,
+}
+
+impl Detector for PasswordDetector {
+    fn name(&self) -> &str {
+        "password"
+    }
+
+    fn action(&self) -> SecretAction {
+        self.action
+    }
+
+    fn find(&self, text: &str) -> Vec<Range<usize>> {
+        let trimmed = text.trim();
+        let looks_like_password = PASSWORD_LENGTH.contains(&trimmed.chars().count())
+            && !trimmed.chars().any(char::is_whitespace)
+            && !trimmed.contains("://")
+            && trimmed.chars().any(|c| c.is_ascii_lowercase())
+            && trimmed.chars().any(|c| c.is_ascii_uppercase())
+            && trimmed.chars().any(|c| c.is_ascii_digit())
+            // 식별자나 경로에 흔한 문자는 특수문자로 치지 않음
+            && trimmed.chars().any(|c| c.is_ascii_punctuation() && !matches!(c, '_' | '-' | '.' | '/' | '\\'));
+        if !looks_like_password {
+            return Vec::new();
+        }
+        let start = text.len() - text.trim_start().len();
+        vec![Range { start, end: start + trimmed.len() }]
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SecretMatch {
+    pub detector: String,
+    pub range: Range<usize>,
+}
+
+// scan 결과: 적용할 action과 일치한 구간들 (시작 위치 순)
+#[derive(Clone, PartialEq, Debug)]
+pub struct Detection {
+    pub action: SecretAction,
+    pub matches: Vec<SecretMatch>,
+}
+
+impl Detection {
+    pub fn detectors(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for found in &self.matches {
+            if !names.contains(&found.detector.as_str()) {
+                names.push(&found.detector);
+            }
+        }
+        names
+    }
+
+    // 일치한 구간을 detector 이름으로 바꾼 본문 (겹친 구간은 앞의 것으로 합침)
+    pub fn mask(&self, text: &str) -> String {
+        let mut masked = String::with_capacity(text.len());
+        let mut end = 0;
+        for found in &self.matches {
+            if found.range.start < end {
+                end = end.max(found.range.end);
+                continue;
+            }
+            masked.push_str(&text[end..found.range.start]);
+            masked.push_str(&format!("[REDACTED {}]", found.detector));
+            end = found.range.end;
+        }
+        masked.push_str(&text[end..]);
+        masked
+    }
+}
+
+pub struct SecretScanner {
+    detectors: Vec<Box<dyn Detector>>,
+    expiry_minutes: u64,
+}
+
+impl SecretScanner {
+    pub fn new(expiry_minutes: u64) -> Self {
+        SecretScanner { detectors: Vec::new(), expiry_minutes }
+    }
+
+    // 기본 detector와 사용자 정의 detector로 구성, secret_detection이 꺼져 있으면 비어 있음
+    pub fn from_settings(settings: &Settings) -> Self {
+        let mut scanner = Self::new(settings.secret_expiry_minutes);
+        if !settings.secret_detection {
+            return scanner;
+        }
+        let action = settings.secret_action;
+        let builtin: Vec<Box<dyn Detector>> = vec![
+            pattern("aws_access_key", r"\b(?:AKIA|ASIA|ABIA|ACCA)[0-9A-Z]{16}\b", action, None),
+            pattern("aws_secret_key", r#"(?i)aws_?secret_?access_?key["']?\s*[:=]\s*["']?[A-Za-z0-9/+=]{40}"#, action, None),
+            pattern("jwt", r"\beyJ[A-Za-z0-9_-]{5,}\.eyJ[A-Za-z0-9_-]{5,}\.[A-Za-z0-9_-]{10,}", action, None),
+            pattern("private_key", r"(?s)-----BEGIN [A-Z0-9 ]*PRIVATE KEY-----.*?(?:-----END [A-Z0-9 ]*PRIVATE KEY-----|\z)", action, None),
+            pattern("credit_card", r"\b\d(?:[ -]?\d){12,18}\b", action, Some(luhn_valid)),
+            Box::new(PasswordDetector { action }),
+        ];
+        for detector in builtin {
+            if !settings.disabled_secret_detectors.iter().any(|name| name == detector.name()) {
+                scanner = scanner.with_detector(detector);
+            }
+        }
+        for user in &settings.secret_patterns {
+            match Regex::new(&user.pattern) {
+                Ok(regex) => {
+                    scanner = scanner.with_detector(Box::new(PatternDetector {
+                        name: user.name.clone(),
+                        regex,
+                        action: user.action.unwrap_or(action),
+                        validate: None,
+                    }));
+                }
+                Err(e) => warn!("Ignoring invalid secret pattern {}: {}", user.name, e),
+            }
+        }
+        scanner
+    }
+
+    pub fn with_detector(mut self, detector: Box<dyn Detector>) -> Self {
+        self.detectors.push(detector);
+        self
+    }
+
+    // 어느 detector와도 일치하지 않으면 None
+    pub fn scan(&self, text: &str) -> Option<Detection> {
+        let mut action = None;
+        let mut matches = Vec::new();
+        for detector in &self.detectors {
+            let found = detector.find(text);
+            if found.is_empty() {
+                continue;
+            }
+            action = action.max(Some(detector.action()));
+            matches.extend(found.into_iter().map(|range| SecretMatch { detector: detector.name().to_string(), range }));
+        }
+        matches.sort_by_key(|found| (found.range.start, std::cmp::Reverse(found.range.end)));
+        Some(Detection { action: action?, matches })
+    }
+
+    // SecretAction::Expire로 저장할 항목의 만료 시각(ms)
+    pub fn expires_at(&self) -> i64 {
+        Local::now().timestamp_millis() + self.expiry_minutes as i64 * MILLIS_PER_MINUTE
+    }
+}
+
+fn pattern(name: &str, pattern: &str, action: SecretAction, validate: Option<fn(&str) -> bool>) -> Box<dyn Detector> {
+    Box::new(PatternDetector {
+        name: name.to_string(),
+        regex: Regex::new(pattern).expect("Invalid built-in secret pattern"),
+        action,
+        validate,
+    })
+}
+
+// 카드 번호 체크섬 (구분자 제외 13~19자리)
+fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+    let sum: u32 = digits.iter()
+        .rev()
+        .enumerate()
+        .map(|(index, &digit)| match index % 2 {
+            0 => digit,
+            _ if digit * 2 > 9 => digit * 2 - 9,
+            _ => digit * 2,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
@@ -1,20 +1,178 @@
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use warp::Filter;
+use warp::Reply;
 use serde::{Deserialize, Serialize};
-use crate::database::ClipboardData;
-use log::info;
+use crate::database::{normalize_folder, ClipboardData, ClipboardKind, ClipboardQuery, Cursor, DerivedKey, EncryptionError, EncryptionKey, LabelFilter, MemoEdit, MemoQuery, MemoRecord, MemoSort, PinnedFilter};
+use crate::image_data;
+use crate::maintenance::{self, RetentionPolicy};
+use log::{info, error};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
 
 #[derive(Deserialize)]
 struct MemoRequest {
     memo: String,
 }
 
+// POST /memo - 본문 외의 항목은 생략 가능
 #[derive(Deserialize)]
-struct UpdateMemoRequest {
-    sequence: u64,
+struct AddMemoRequest {
     memo: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    folder: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+// PUT /memo - 생략한 항목은 그대로, 빈 문자열이면 지움
+#[derive(Deserialize)]
+struct UpdateMemoRequest {
+    #[serde(alias = "sequence")]
+    id: u64,
+    #[serde(default)]
+    memo: Option<String>,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    folder: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MemoFolderRequest {
+    #[serde(default)]
+    folder: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RenameFolderRequest {
+    from: String,
+    to: String,
+}
+
+#[derive(Deserialize)]
+struct MemoOrderRequest {
+    ids: Vec<u64>,
+}
+
+#[derive(Deserialize)]
+struct ContentRequest {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct TagsRequest {
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct NameRequest {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CollectionItemRequest {
+    id: u64,
+}
+
+// 암호화 키: key_file이 true이면 설정(encryption_key_file)의 키 파일, 아니면 passphrase
+// 클라이언트가 준 경로의 파일은 읽지 않는다.
+#[derive(Deserialize, Default)]
+struct KeyRequest {
+    #[serde(default)]
+    passphrase: Option<String>,
+    #[serde(default)]
+    key_file: bool,
+}
+
+impl KeyRequest {
+    fn key(self, key_file: Option<&str>) -> Option<EncryptionKey> {
+        if self.key_file {
+            return key_file.map(|path| EncryptionKey::KeyFile(path.to_string()));
+        }
+        self.passphrase.map(EncryptionKey::Passphrase)
+    }
 }
 
+// 지금 키를 아는지 확인할 때는 passphrase나 키 파일 내용(base64)을 직접 받음
+// key_file: true는 서버가 자기 설정으로 채우므로 요청한 쪽이 키를 안다는 증명이 되지 못한다.
+#[derive(Deserialize, Default)]
+struct CurrentKeyRequest {
+    #[serde(default)]
+    passphrase: Option<String>,
+    #[serde(default)]
+    key_file_contents: Option<String>,
+}
+
+impl CurrentKeyRequest {
+    fn key(self) -> Result<Option<EncryptionKey>, String> {
+        if let Some(encoded) = self.key_file_contents {
+            return BASE64.decode(encoded)
+                .map(|contents| Some(EncryptionKey::KeyFileContents(contents)))
+                .map_err(|_| "key_file_contents must be base64".to_string());
+        }
+        Ok(self.passphrase.map(EncryptionKey::Passphrase))
+    }
+}
+
+// POST /encryption/rekey - current는 지금 키 (암호화된 DB에서는 필수), 나머지는 새 키 (없으면 복호화)
+#[derive(Deserialize)]
+struct RekeyRequest {
+    #[serde(default)]
+    current: CurrentKeyRequest,
+    #[serde(flatten)]
+    new: KeyRequest,
+}
+
+// 암호화된 DB가 잠겨 있을 때 잠금 해제 외의 요청을 거부
+#[derive(Debug)]
+struct Locked;
+
+impl warp::reject::Reject for Locked {}
+
+// 같은 컴퓨터(loopback)에서 온 요청만 받는 라우트
+#[derive(Debug)]
+struct NotLocal;
+
+impl warp::reject::Reject for NotLocal {}
+
+// passphrase를 맞힐 때까지 반복해 시도하지 못하도록 시도할 때마다 다음 시도까지 기다릴 시간을 두 배로 늘림
+// 진행 중인 시도도 실패로 세고, 키가 맞으면 처음으로 되돌림
+const MAX_KEY_ATTEMPT_DELAY: Duration = Duration::from_secs(300);
+
+#[derive(Default)]
+struct KeyAttempts {
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl KeyAttempts {
+    // 아직 기다려야 하면 남은 시간
+    fn begin(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(retry_at) = self.retry_at.filter(|retry_at| now < *retry_at) {
+            return Err(retry_at - now);
+        }
+        let delay = Duration::from_secs(1 << self.failures.min(9)).min(MAX_KEY_ATTEMPT_DELAY);
+        self.failures += 1;
+        self.retry_at = Some(now + delay);
+        Ok(())
+    }
+
+    fn succeed(&mut self) {
+        *self = KeyAttempts::default();
+    }
+}
+
+// 브라우저(Origin 헤더가 있는 요청)에서 온 암호화 관련 요청을 거부
+#[derive(Debug)]
+struct FromBrowser;
+
+impl warp::reject::Reject for FromBrowser {}
+
 #[derive(Serialize)]
 struct ApiResponse {
     success: bool,
@@ -42,56 +200,460 @@ impl ApiResponse {
 
 pub async fn start_server(
     clipboard_data: Arc<Mutex<ClipboardData>>, 
-    port: u16
+    port: u16,
+    retention: RetentionPolicy,
+    key_file: Option<String>, // 설정의 encryption_key_file (POST /unlock, /encryption/rekey 새 키의 key_file: true)
 ) {
     // GET /clipboard - 클립보드 항목들 조회
+    // count, offset, before/after(커서), since/until(ms), kind(쉼표로 구분) 파라미터 지원
+    // pinned=first 이면 고정된 항목을 먼저, pinned=only 이면 고정된 항목만
+    // tag(쉼표로 구분, 모두 붙은 항목), collection 파라미터로 태그/컬렉션 필터 (GET /memo, GET /search도 동일)
     let clipboard_data_filter = warp::any().map(move || clipboard_data.clone());
+    let retention_filter = warp::any().map(move || retention);
+    let key_file_filter = warp::any().map(move || key_file.clone());
+    let key_attempts = Arc::new(Mutex::new(KeyAttempts::default()));
+    let key_attempts_filter = warp::any().map(move || key_attempts.clone());
+    let local_only = warp::addr::remote()
+        .and_then(require_local)
+        .untuple_one();
+    let unlocked = clipboard_data_filter.clone()
+        .and_then(require_unlocked)
+        .untuple_one();
+    let no_origin = warp::header::optional::<String>("origin")
+        .and_then(require_no_origin)
+        .untuple_one();
     
     let get_clipboard = warp::path("clipboard")
+        .and(warp::path::end())
         .and(warp::get())
         .and(warp::query::<std::collections::HashMap<String, String>>())
         .and(clipboard_data_filter.clone())
         .and_then(handle_get_clipboard);
 
-    // GET /memo - 메모 항목들 조회
+    // GET /clipboard/{id}/image - 이미지 항목의 PNG 조회 (max_size 파라미터로 썸네일)
+    let get_clipboard_image = warp::path("clipboard")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("image"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<std::collections::HashMap<String, String>>())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_get_clipboard_image);
+
+    // POST /clipboard/{id}/activate - 항목을 시스템 클립보드에 다시 설정
+    // format=plain 이면 HTML이 있어도 plain text로 설정 (기본값: rich)
+    let activate_clipboard = warp::path("clipboard")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("activate"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::query::<std::collections::HashMap<String, String>>())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_activate_clipboard);
+
+    // POST /clipboard/{id}/pin - 항목 고정 (max_items 정리 대상에서 제외)
+    // DELETE /clipboard/{id}/pin - 고정 해제
+    let pin_clipboard = warp::path("clipboard")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("pin"))
+        .and(warp::path::end())
+        .and(warp::post().map(|| true).or(warp::delete().map(|| false)).unify())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_pin_clipboard);
+
+    // PUT /clipboard/{id} - 텍스트 항목의 본문 수정 ({"content"}, HTML 서식은 버림)
+    let update_clipboard = warp::path("clipboard")
+        .and(warp::path::param::<u64>())
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_update_clipboard);
+
+    // DELETE /clipboard/{id} - 항목을 휴지통으로 옮김 (붙은 메모는 일반 메모로 남음)
+    let delete_clipboard = warp::path("clipboard")
+        .and(warp::path::param::<u64>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_delete_clipboard);
+
+    // DELETE /clipboard?since=&until= - 복사 시각(ms)이 [since, until) 범위인 항목 삭제 (둘 중 하나는 필수)
+    // DELETE /clipboard/all - 기록 전체 삭제 (메모는 유지)
+    // 삭제한 항목은 휴지통으로 옮겨지며 POST /trash/{id}/restore로 복원할 수 있음
+    // 둘 다 고정된 항목은 남기며, include_pinned=true 이면 함께 삭제
+    let delete_clipboard_range = warp::path("clipboard")
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(warp::query::<std::collections::HashMap<String, String>>())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_delete_clipboard_range);
+
+    let clear_clipboard = warp::path("clipboard")
+        .and(warp::path("all"))
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(warp::query::<std::collections::HashMap<String, String>>())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_clear_clipboard);
+
+    // PUT /clipboard/{id}/memo - 항목에 메모 추가/수정 ({"memo"})
+    // DELETE /clipboard/{id}/memo - 항목에 붙은 메모 삭제
+    let set_clipboard_memo = warp::path("clipboard")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("memo"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_set_clipboard_memo);
+
+    let delete_clipboard_memo = warp::path("clipboard")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("memo"))
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_delete_clipboard_memo);
+
+    // GET /search?q= - 클립보드 항목과 메모 전문 검색 (count: 종류별 최대 개수)
+    // fuzzy=true 이면 fzf 방식 부분 일치, regex=true 이면 정규식 검색
+    let search = warp::path("search")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(warp::query::<std::collections::HashMap<String, String>>())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_search);
+
+    // GET /memo/{id}/revisions - 메모의 이전 버전 (최근 버전부터)
+    let get_memo_revisions = warp::path("memo")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("revisions"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_get_memo_revisions);
+
+    // POST /memo/{id}/revisions/{revision}/restore - 메모를 이전 버전의 내용으로 되돌림
+    let restore_memo_revision = warp::path("memo")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("revisions"))
+        .and(warp::path::param::<u64>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_restore_memo_revision);
+
+    // GET /memo/folders - 메모 폴더 목록 (하위 폴더의 메모까지 센 개수 포함)
+    let get_memo_folders = warp::path("memo")
+        .and(warp::path("folders"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_get_memo_folders);
+
+    // PUT /memo/folders - 폴더 이름 변경/이동 ({"from", "to"}, 하위 폴더 포함, to가 빈 경로면 최상위로)
+    let rename_memo_folder = warp::path("memo")
+        .and(warp::path("folders"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_rename_memo_folder);
+
+    // PUT /memo/{id}/folder - 메모를 폴더로 옮김 ({"folder"}, 생략하거나 빈 경로면 최상위)
+    let move_memo = warp::path("memo")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("folder"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_move_memo);
+
+    // GET /memo - 메모 항목들 조회 (sort=created|updated|manual, 기본 created)
+    // folder 파라미터가 있으면 그 폴더에 바로 들어 있는 메모만 (빈 값이면 최상위)
     let get_memo = warp::path("memo")
         .and(warp::get())
         .and(warp::query::<std::collections::HashMap<String, String>>())
         .and(clipboard_data_filter.clone())
         .and_then(handle_get_memo);
 
-    // POST /memo - 사용자 정의 메모 추가
+    // POST /memo - 사용자 정의 메모 추가 ({"memo", "title", "folder", "description"})
     let add_memo = warp::path("memo")
         .and(warp::post())
         .and(warp::body::json())
         .and(clipboard_data_filter.clone())
         .and_then(handle_add_memo);
 
-    // PUT /memo - 기존 항목에 메모 추가/수정
+    // PUT /memo/order - 메모를 주어진 순서로 배치 ({"ids": [...]}, sort=manual에서 사용)
+    let reorder_memos = warp::path("memo")
+        .and(warp::path("order"))
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_reorder_memos);
+
+    // PUT /memo - 기존 메모 수정 ({"id", "memo", "title", "folder", "description"})
     let update_memo = warp::path("memo")
         .and(warp::put())
         .and(warp::body::json())
         .and(clipboard_data_filter.clone())
         .and_then(handle_update_memo);
 
-    // DELETE /memo/{sequence} - 메모 삭제
+    // DELETE /memo/{id} - 메모를 휴지통으로 옮김
     let delete_memo = warp::path("memo")
         .and(warp::path::param::<u64>())
         .and(warp::delete())
         .and(clipboard_data_filter.clone())
         .and_then(handle_delete_memo);
 
+    // GET /trash - 휴지통의 클립보드 항목과 메모 (최근에 삭제한 항목부터)
+    let get_trash = warp::path("trash")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_get_trash);
+
+    // POST /trash/{id}/restore - 휴지통의 항목을 같은 id로 복원
+    let restore_trash = warp::path("trash")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_restore_trash);
+
+    // DELETE /trash/{id} - 휴지통의 항목을 완전히 삭제
+    let delete_trash = warp::path("trash")
+        .and(warp::path::param::<u64>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_delete_trash);
+
+    // DELETE /trash - 휴지통 비우기 (trash_retention_days가 지난 항목은 자동으로 비움)
+    let empty_trash = warp::path("trash")
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_empty_trash);
+
+    // GET /stats/storage - 항목 수와 저장 용량 사용량
+    let storage_stats = warp::path("stats")
+        .and(warp::path("storage"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_storage_stats);
+
+    // POST /maintenance/prune - 보관 기간이 지난 항목 정리 (고정된 항목 제외)
+    // days=N 이면 설정 대신 N일보다 오래된 항목을 정리 (N은 최대 36500)
+    let prune = warp::path("maintenance")
+        .and(warp::path("prune"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::query::<std::collections::HashMap<String, String>>())
+        .and(retention_filter)
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_prune);
+
+    // GET /tags - 사용 중인 태그와 태그별 항목 수
+    let get_tags = warp::path("tags")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_get_tags);
+
+    // PUT /tags/{name} - 태그 이름 변경 ({"name": 새 이름})
+    let rename_tag = warp::path("tags")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_rename_tag);
+
+    // DELETE /tags/{name} - 태그를 모든 항목에서 제거
+    let delete_tag = warp::path("tags")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_delete_tag);
+
+    // POST /items/{id}/tags - 클립보드 항목이나 메모에 태그 추가 ({"tags": [...]})
+    // PUT /items/{id}/tags - 태그 목록 교체
+    let set_item_tags = warp::path("items")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(warp::post().map(|| false).or(warp::put().map(|| true)).unify())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_set_item_tags);
+
+    // DELETE /items/{id}/tags/{name} - 항목에서 태그 제거
+    let remove_item_tag = warp::path("items")
+        .and(warp::path::param::<u64>())
+        .and(warp::path("tags"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_remove_item_tag);
+
+    // GET /collections - 컬렉션과 담긴 항목 수
+    let get_collections = warp::path("collections")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_get_collections);
+
+    // POST /collections - 컬렉션 생성 ({"name"})
+    let create_collection = warp::path("collections")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_create_collection);
+
+    // PUT /collections/{name} - 컬렉션 이름 변경 ({"name": 새 이름})
+    let rename_collection = warp::path("collections")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::put())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_rename_collection);
+
+    // DELETE /collections/{name} - 컬렉션 삭제 (담긴 항목은 유지)
+    let delete_collection = warp::path("collections")
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_delete_collection);
+
+    // POST /collections/{name}/items - 컬렉션에 항목 추가 ({"id"})
+    let add_collection_item = warp::path("collections")
+        .and(warp::path::param::<String>())
+        .and(warp::path("items"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_add_collection_item);
+
+    // DELETE /collections/{name}/items/{id} - 컬렉션에서 항목 제거
+    let remove_collection_item = warp::path("collections")
+        .and(warp::path::param::<String>())
+        .and(warp::path("items"))
+        .and(warp::path::param::<u64>())
+        .and(warp::path::end())
+        .and(warp::delete())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_remove_collection_item);
+
+    // GET /encryption - 암호화 여부와 잠김 상태
+    let get_encryption = warp::path("encryption")
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_get_encryption);
+
+    // POST /unlock - 암호화된 DB 잠금 해제 ({"passphrase"} 또는 {"key_file": true}, key_file은 같은 컴퓨터에서 온 요청만)
+    // 잠겨 있는 동안에는 이 두 요청 외에는 모두 "Database is locked" 오류를 반환
+    // 틀린 키로 시도할 때마다 다음 시도까지 기다려야 하는 시간이 늘어남 (/encryption/rekey의 current도 같이 셈)
+    let unlock = warp::path("unlock")
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(warp::addr::remote())
+        .and(warp::body::json())
+        .and(key_file_filter.clone())
+        .and(key_attempts_filter.clone())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_unlock);
+
+    // POST /encryption/rekey - 저장된 값을 새 키로 다시 암호화 ({"current": {...}, "passphrase"} 또는 {"current": {...}, "key_file": true})
+    // current는 {"passphrase"} 또는 {"key_file_contents": base64}, 암호화된 DB에서는 필수
+    // 새 키 없이 ({"current": {...}}) 요청하면 복호화해 평문 DB로 되돌림, 같은 컴퓨터에서 온 요청만 받음
+    let rekey = warp::path("encryption")
+        .and(warp::path("rekey"))
+        .and(warp::path::end())
+        .and(warp::post())
+        .and(local_only)
+        .and(warp::body::json())
+        .and(key_file_filter.clone())
+        .and(key_attempts_filter.clone())
+        .and(clipboard_data_filter.clone())
+        .and_then(handle_rekey);
+
     let cors = warp::cors()
         .allow_any_origin()
         .allow_headers(vec!["content-type"])
         .allow_methods(vec!["GET", "POST", "PUT", "DELETE"]);
 
-    let routes = get_clipboard
+    // 라우트가 많아 하나의 or 체인으로 묶으면 타입이 컴파일러의 재귀 한도를 넘으므로 묶음마다 boxed로 끊음
+    let clipboard_routes = get_clipboard
+        .or(get_clipboard_image)
+        .or(activate_clipboard)
+        .or(pin_clipboard)
+        .or(update_clipboard)
+        .or(delete_clipboard)
+        .or(delete_clipboard_range)
+        .or(clear_clipboard)
+        .or(set_clipboard_memo)
+        .or(delete_clipboard_memo)
+        .or(search)
+        .boxed();
+
+    let memo_routes = get_memo_revisions
+        .or(restore_memo_revision)
+        .or(get_memo_folders)
+        .or(rename_memo_folder)
+        .or(move_memo)
         .or(get_memo)
         .or(add_memo)
+        .or(reorder_memos)
         .or(update_memo)
         .or(delete_memo)
-        .with(cors)
+        .boxed();
+
+    let storage_routes = get_trash
+        .or(restore_trash)
+        .or(delete_trash)
+        .or(empty_trash)
+        .or(storage_stats)
+        .or(prune)
+        .boxed();
+
+    let label_routes = get_tags
+        .or(rename_tag)
+        .or(delete_tag)
+        .or(set_item_tags)
+        .or(remove_item_tag)
+        .or(get_collections)
+        .or(create_collection)
+        .or(rename_collection)
+        .or(delete_collection)
+        .or(add_collection_item)
+        .or(remove_collection_item)
+        .boxed();
+
+    let guarded_routes = clipboard_routes
+        .or(memo_routes)
+        .or(storage_routes)
+        .or(label_routes);
+
+    // 암호화 관련 요청은 브라우저에서 받지 않으므로 CORS를 적용하지 않음
+    let encryption_routes = no_origin
+        .and(get_encryption.or(unlock).or(unlocked.clone().and(rekey)));
+
+    let routes = encryption_routes
+        .or(unlocked.and(guarded_routes).with(cors))
         .recover(handle_rejection);
 
     println!("Starting server on port {}", port);
@@ -99,95 +661,884 @@ pub async fn start_server(
     warp::serve(routes).run(([0, 0, 0, 0], port)).await;
 }
 
+fn is_local(addr: Option<std::net::SocketAddr>) -> bool {
+    addr.is_some_and(|addr| addr.ip().is_loopback())
+}
+
+async fn require_local(addr: Option<std::net::SocketAddr>) -> Result<(), warp::Rejection> {
+    if !is_local(addr) {
+        return Err(warp::reject::custom(NotLocal));
+    }
+    Ok(())
+}
+
+async fn require_no_origin(origin: Option<String>) -> Result<(), warp::Rejection> {
+    if origin.is_some() {
+        return Err(warp::reject::custom(FromBrowser));
+    }
+    Ok(())
+}
+
+async fn require_unlocked(clipboard_data: Arc<Mutex<ClipboardData>>) -> Result<(), warp::Rejection> {
+    if clipboard_data.lock().unwrap().is_locked() {
+        return Err(warp::reject::custom(Locked));
+    }
+    Ok(())
+}
+
 async fn handle_get_clipboard(
     query: std::collections::HashMap<String, String>,
     clipboard_data: Arc<Mutex<ClipboardData>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let count = query.get("count")
-        .and_then(|c| c.parse::<usize>().ok());
+    let query = match parse_clipboard_query(&query) {
+        Ok(query) => query,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::error(&message))),
+    };
 
     let clipboard_data = clipboard_data.lock().unwrap();
-    let items = clipboard_data.get_clipboard_items(count);
+    let page = clipboard_data.query_clipboard_items(&query);
 
     info!("------------------------------------------------");
-    for item in items.clone() {
-        info!("Clipboard data: {}-{}: \"{}\"", item.date, item.sequence, item.content);
+    for item in page.items.iter() {
+        let content = if item.sensitive { "<sensitive>" } else { item.content.as_str() };
+        info!("Clipboard data: {} ({}): \"{}\"", item.id, item.date, content);
     }
     info!("------------------------------------------------");
 
     let response = ApiResponse::success(
         "Clipboard items retrieved successfully",
-        Some(serde_json::to_value(&items).unwrap()),
+        Some(serde_json::to_value(&page).unwrap()),
     );
     
     Ok(warp::reply::json(&response))
 }
 
-async fn handle_get_memo(
+fn parse_clipboard_query(query: &std::collections::HashMap<String, String>) -> Result<ClipboardQuery, String> {
+    fn parse_cursor(query: &std::collections::HashMap<String, String>, name: &str) -> Result<Option<Cursor>, String> {
+        query.get(name)
+            .map(|value| Cursor::parse(value).ok_or_else(|| format!("Invalid {} cursor: {}", name, value)))
+            .transpose()
+    }
+
+    let kinds = match query.get("kind") {
+        Some(kinds) => kinds.split(',')
+            .map(|kind| kind.trim().parse::<ClipboardKind>())
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
+    };
+
+    Ok(ClipboardQuery {
+        count: parse(query, "count")?,
+        offset: parse(query, "offset")?.unwrap_or(0),
+        before: parse_cursor(query, "before")?,
+        after: parse_cursor(query, "after")?,
+        since: parse(query, "since")?,
+        until: parse(query, "until")?,
+        kinds,
+        pinned: parse::<PinnedFilter>(query, "pinned")?.unwrap_or_default(),
+        labels: parse_label_filter(query),
+    })
+}
+
+fn parse<T: std::str::FromStr>(query: &std::collections::HashMap<String, String>, name: &str) -> Result<Option<T>, String> {
+    query.get(name)
+        .map(|value| value.parse::<T>().map_err(|_| format!("Invalid {}: {}", name, value)))
+        .transpose()
+}
+
+fn parse_label_filter(query: &std::collections::HashMap<String, String>) -> LabelFilter {
+    LabelFilter::parse(
+        query.get("tag").map(String::as_str),
+        query.get("collection").map(String::as_str),
+    )
+}
+
+// 경로의 태그/컬렉션 이름은 퍼센트 인코딩되어 있음
+fn decode_name(name: &str) -> String {
+    percent_encoding::percent_decode_str(name).decode_utf8_lossy().to_string()
+}
+
+async fn handle_get_clipboard_image(
+    id: u64,
+    query: std::collections::HashMap<String, String>,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<warp::reply::Response, warp::Rejection> {
+    let max_size = query.get("max_size")
+        .and_then(|s| s.parse::<u32>().ok());
+
+    let png = {
+        let clipboard_data = clipboard_data.lock().unwrap();
+        clipboard_data.read_image(id)
+    };
+    let Some(png) = png else {
+        return Ok(warp::reply::json(&ApiResponse::error("Image not found")).into_response());
+    };
+
+    let png = match max_size {
+        Some(max_size) => match image_data::thumbnail_png(&png, max_size) {
+            Ok(thumbnail) => thumbnail,
+            Err(e) => {
+                error!("Failed to create thumbnail for {}: {}", id, e);
+                return Ok(warp::reply::json(&ApiResponse::error("Failed to create thumbnail")).into_response());
+            }
+        },
+        None => png,
+    };
+
+    Ok(warp::reply::with_header(png, "content-type", "image/png").into_response())
+}
+
+async fn handle_activate_clipboard(
+    id: u64,
     query: std::collections::HashMap<String, String>,
     clipboard_data: Arc<Mutex<ClipboardData>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    let count = query.get("count")
-        .and_then(|c| c.parse::<usize>().ok());
+    let plain = query.get("format").is_some_and(|format| format == "plain");
 
-    let clipboard_data = clipboard_data.lock().unwrap();
-    let items = clipboard_data.get_memo_items(count);
+    let (item, png) = {
+        let clipboard_data = clipboard_data.lock().unwrap();
+        let item = clipboard_data.get_full_clipboard_item(id);
+        let png = clipboard_data.read_image(id);
+        (item, png)
+    };
+    let Some(item) = item else {
+        return Ok(warp::reply::json(&ApiResponse::error("Clipboard item not found")));
+    };
 
-    info!("------------------------------------------------");
-    for item in items.iter() {
-        info!("Memo data: {}: \"{}\"", item.sequence, item.memo);
-    }
-    info!("------------------------------------------------");
+    let result = arboard::Clipboard::new().and_then(|mut clipboard| {
+        match (item.kind, png) {
+            (ClipboardKind::Files, _) => {
+                // 이미 삭제된 파일은 제외하고 남아있는 파일만 설정
+                let paths: Vec<String> = item.files.unwrap_or_default()
+                    .into_iter()
+                    .filter(|file| file.exists)
+                    .map(|file| file.path)
+                    .collect();
+                if paths.is_empty() {
+                    Err(arboard::Error::Unknown { description: "None of the copied files exist anymore".to_string() })
+                } else {
+                    clipboard.set().file_list(&paths)
+                }
+            }
+            (ClipboardKind::Image, Some(png)) => match image_data::decode_png(&png) {
+                Ok(image) => clipboard.set_image(image),
+                Err(e) => Err(arboard::Error::Unknown { description: e.to_string() }),
+            },
+            _ => match item.html {
+                Some(html) if !plain => clipboard.set_html(html, Some(item.content)),
+                _ => clipboard.set_text(item.content),
+            },
+        }
+    });
 
-    let response = ApiResponse::success(
-        "Memo items retrieved successfully",
-        Some(serde_json::to_value(&items).unwrap()),
-    );
-    
+    let response = match result {
+        Ok(()) => {
+            clipboard_data.lock().unwrap().record_use(id);
+            ApiResponse::success("Clipboard item activated", None)
+        }
+        Err(e) => {
+            error!("Failed to set system clipboard: {}", e);
+            ApiResponse::error(&format!("Failed to set system clipboard: {}", e))
+        }
+    };
     Ok(warp::reply::json(&response))
 }
 
-async fn handle_add_memo(
-    request: MemoRequest,
+async fn handle_pin_clipboard(
+    id: u64,
+    pinned: bool,
     clipboard_data: Arc<Mutex<ClipboardData>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let clipboard_data = clipboard_data.lock().unwrap();
-    let key = clipboard_data.add_memo(&request.memo);
-    
-    let response = ApiResponse::success(
-        "Custom memo added successfully",
-        Some(serde_json::json!({"key": key})),
-    );
+    if !clipboard_data.set_pinned(id, pinned) {
+        return Ok(warp::reply::json(&ApiResponse::error("Clipboard item not found")));
+    }
+    info!("Clipboard item {} {}", id, if pinned { "pinned" } else { "unpinned" });
+
+    let message = if pinned { "Clipboard item pinned" } else { "Clipboard item unpinned" };
+    let response = ApiResponse::success(message, Some(serde_json::json!({ "id": id, "pinned": pinned })));
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_update_clipboard(
+    id: u64,
+    request: ContentRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.update_item(id, &request.content) {
+        return Ok(warp::reply::json(&ApiResponse::error("Clipboard item not found, not a text item, or too large")));
+    }
+    info!("Clipboard item {} updated", id);
+
+    let response = ApiResponse::success(
+        "Clipboard item updated",
+        clipboard_data.get_clipboard_item(id).map(|item| serde_json::to_value(&item).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_delete_clipboard(
+    id: u64,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.delete_item(id) {
+        return Ok(warp::reply::json(&ApiResponse::error("Clipboard item not found")));
+    }
+    info!("Clipboard item {} deleted", id);
+
+    let response = ApiResponse::success("Clipboard item deleted", Some(serde_json::json!({ "id": id })));
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_delete_clipboard_range(
+    query: std::collections::HashMap<String, String>,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let (since, until) = match (parse::<i64>(&query, "since"), parse::<i64>(&query, "until")) {
+        (Ok(None), Ok(None)) => {
+            return Ok(warp::reply::json(&ApiResponse::error("since or until is required; use DELETE /clipboard/all to clear history")));
+        }
+        (Ok(since), Ok(until)) => (since, until),
+        (Err(message), _) | (_, Err(message)) => return Ok(warp::reply::json(&ApiResponse::error(&message))),
+    };
+    let include_pinned = query.get("include_pinned").is_some_and(|value| value == "true");
+
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let deleted = clipboard_data.delete_range(since, until, include_pinned);
+    info!("Deleted {} clipboard items in range {:?}..{:?}", deleted, since, until);
+
+    let response = ApiResponse::success("Clipboard items deleted", Some(serde_json::json!({ "count": deleted })));
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_clear_clipboard(
+    query: std::collections::HashMap<String, String>,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let include_pinned = query.get("include_pinned").is_some_and(|value| value == "true");
+
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let deleted = clipboard_data.clear_history(include_pinned);
+    info!("Cleared clipboard history: {} items deleted", deleted);
+
+    let response = ApiResponse::success("Clipboard history cleared", Some(serde_json::json!({ "count": deleted })));
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_set_clipboard_memo(
+    id: u64,
+    request: MemoRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let Some(memo_id) = clipboard_data.set_item_memo(id, &request.memo) else {
+        return Ok(warp::reply::json(&ApiResponse::error("Clipboard item not found")));
+    };
+    info!("Memo {} attached to clipboard item {}", memo_id, id);
+
+    let response = ApiResponse::success(
+        "Clipboard item memo saved",
+        Some(serde_json::json!({ "id": id, "memo_id": memo_id })),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_delete_clipboard_memo(
+    id: u64,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.remove_item_memo(id) {
+        return Ok(warp::reply::json(&ApiResponse::error("Clipboard item has no memo")));
+    }
+
+    let response = ApiResponse::success("Clipboard item memo deleted", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_search(
+    query: std::collections::HashMap<String, String>,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let Some(q) = query.get("q") else {
+        return Ok(warp::reply::json(&ApiResponse::error("Missing query parameter: q")));
+    };
+    let count = query.get("count")
+        .and_then(|c| c.parse::<usize>().ok());
+
+    let fuzzy = query.get("fuzzy").is_some_and(|value| value == "true");
+    let regex = query.get("regex").is_some_and(|value| value == "true");
+    let labels = parse_label_filter(&query);
+
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let results = match (fuzzy, regex) {
+        (true, true) => {
+            return Ok(warp::reply::json(&ApiResponse::error("fuzzy and regex cannot be used together")));
+        }
+        (true, false) => clipboard_data.fuzzy_search(q, count, &labels),
+        (false, true) => match clipboard_data.regex_search(q, count, &labels) {
+            Ok(results) => results,
+            Err(e) => return Ok(warp::reply::json(&ApiResponse::error(&format!("Invalid regex: {}", e)))),
+        },
+        (false, false) => clipboard_data.search(q, count, &labels),
+    };
+    info!("Search \"{}\": {} clipboard items, {} memos", q, results.clipboard.len(), results.memos.len());
+
+    let response = ApiResponse::success(
+        "Search completed successfully",
+        Some(serde_json::to_value(&results).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_get_memo(
+    query: std::collections::HashMap<String, String>,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let count = query.get("count")
+        .and_then(|c| c.parse::<usize>().ok());
+    let sort = match parse::<MemoSort>(&query, "sort") {
+        Ok(sort) => sort.unwrap_or_default(),
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::error(&message))),
+    };
+    let memo_query = MemoQuery {
+        count,
+        sort,
+        folder: query.get("folder").map(|folder| normalize_folder(folder).unwrap_or_default()),
+        labels: parse_label_filter(&query),
+    };
+
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let items = clipboard_data.query_memo_items(&memo_query);
+
+    info!("------------------------------------------------");
+    for item in items.iter() {
+        info!("Memo data: {}: {} bytes", item.id, item.memo.len());
+    }
+    info!("------------------------------------------------");
+
+    let response = ApiResponse::success(
+        "Memo items retrieved successfully",
+        Some(serde_json::to_value(&items).unwrap()),
+    );
     
     Ok(warp::reply::json(&response))
 }
 
+async fn handle_add_memo(
+    request: AddMemoRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let Some(id) = clipboard_data.add_memo_record(&MemoRecord {
+        memo: request.memo,
+        title: request.title,
+        folder: request.folder,
+        description: request.description,
+    }) else {
+        return Ok(warp::reply::json(&ApiResponse::error("Database is locked")));
+    };
+    
+    let response = ApiResponse::success(
+        "Custom memo added successfully",
+        Some(serde_json::json!({"id": id})),
+    );
+    
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_get_memo_revisions(
+    id: u64,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let Some(revisions) = clipboard_data.memo_revisions(id) else {
+        return Ok(warp::reply::json(&ApiResponse::error("Memo not found")));
+    };
+
+    let response = ApiResponse::success(
+        "Memo revisions retrieved successfully",
+        Some(serde_json::to_value(&revisions).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_restore_memo_revision(
+    id: u64,
+    revision: u64,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.restore_memo_revision(id, revision) {
+        return Ok(warp::reply::json(&ApiResponse::error("Memo revision not found")));
+    }
+    info!("Memo {} restored to revision {}", id, revision);
+
+    let response = ApiResponse::success("Memo revision restored successfully", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_reorder_memos(
+    request: MemoOrderRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.reorder_memos(&request.ids) {
+        return Ok(warp::reply::json(&ApiResponse::error("Memo not found")));
+    }
+
+    let response = ApiResponse::success("Memos reordered successfully", None);
+    Ok(warp::reply::json(&response))
+}
+
 async fn handle_update_memo(
     request: UpdateMemoRequest,
     clipboard_data: Arc<Mutex<ClipboardData>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
-    info!("Updating memo - sequence: {}, memo: '{}'", request.sequence, request.memo);
+    info!("Updating memo - id: {}, memo bytes: {:?}", request.id, request.memo.as_ref().map(String::len));
     
     let clipboard_data = clipboard_data.lock().unwrap();
-    clipboard_data.update_memo(request.sequence, &request.memo);
+    let edit = MemoEdit {
+        memo: request.memo,
+        title: request.title,
+        folder: request.folder,
+        description: request.description,
+    };
+    if !clipboard_data.edit_memo(request.id, &edit) {
+        return Ok(warp::reply::json(&ApiResponse::error("Memo not found")));
+    }
     
     info!("Memo updated successfully");
     let response = ApiResponse::success("Memo updated successfully", None);
     Ok(warp::reply::json(&response))
 }
 
+async fn handle_get_memo_folders(
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let folders = clipboard_data.memo_folders();
+
+    let response = ApiResponse::success(
+        "Memo folders retrieved successfully",
+        Some(serde_json::to_value(&folders).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_rename_memo_folder(
+    request: RenameFolderRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let moved = clipboard_data.rename_memo_folder(&request.from, &request.to);
+    if moved == 0 {
+        return Ok(warp::reply::json(&ApiResponse::error("Folder not found")));
+    }
+    info!("Memo folder \"{}\" renamed to \"{}\" ({} memos)", request.from, request.to, moved);
+
+    let response = ApiResponse::success(
+        "Memo folder renamed successfully",
+        Some(serde_json::json!({ "moved": moved })),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_move_memo(
+    id: u64,
+    request: MemoFolderRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.move_memo(id, request.folder.as_deref()) {
+        return Ok(warp::reply::json(&ApiResponse::error("Memo not found")));
+    }
+
+    let response = ApiResponse::success("Memo moved successfully", None);
+    Ok(warp::reply::json(&response))
+}
+
 async fn handle_delete_memo(
-    sequence: u64,
+    id: u64,
     clipboard_data: Arc<Mutex<ClipboardData>>,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let clipboard_data = clipboard_data.lock().unwrap();
-    clipboard_data.delete_memo(sequence);
+    clipboard_data.delete_memo(id);
     
     let response = ApiResponse::success("Memo deleted successfully", None);
     Ok(warp::reply::json(&response))
 }
 
+async fn handle_get_trash(
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let items = clipboard_data.list_trash();
+
+    let response = ApiResponse::success(
+        "Trash items retrieved successfully",
+        Some(serde_json::to_value(&items).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_restore_trash(
+    id: u64,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.restore_from_trash(id) {
+        return Ok(warp::reply::json(&ApiResponse::error("Item not found in trash")));
+    }
+    info!("Item {} restored from trash", id);
+
+    let response = ApiResponse::success("Item restored", Some(serde_json::json!({ "id": id })));
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_delete_trash(
+    id: u64,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.delete_from_trash(id) {
+        return Ok(warp::reply::json(&ApiResponse::error("Item not found in trash")));
+    }
+    info!("Item {} permanently deleted from trash", id);
+
+    let response = ApiResponse::success("Item permanently deleted", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_empty_trash(
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let purged = clipboard_data.purge_trash(None);
+    info!("Emptied trash: {} items deleted", purged);
+
+    let response = ApiResponse::success("Trash emptied", Some(serde_json::json!({ "count": purged })));
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_storage_stats(
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let stats = clipboard_data.storage_stats();
+
+    let response = ApiResponse::success(
+        "Storage stats retrieved successfully",
+        Some(serde_json::to_value(&stats).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_prune(
+    query: std::collections::HashMap<String, String>,
+    retention: RetentionPolicy,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let now = chrono::Local::now();
+    let cutoff = match parse::<u64>(&query, "days") {
+        Ok(Some(days)) if days > maintenance::MAX_PRUNE_DAYS => {
+            let message = format!("Invalid days: {} (max {})", days, maintenance::MAX_PRUNE_DAYS);
+            return Ok(warp::reply::json(&ApiResponse::error(&message)));
+        }
+        Ok(Some(days)) => match maintenance::cutoff_days_ago(now, days) {
+            Some(cutoff) => Some(cutoff),
+            None => return Ok(warp::reply::json(&ApiResponse::error(&format!("Invalid days: {}", days)))),
+        },
+        Ok(None) => retention.cutoff(now),
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::error(&message))),
+    };
+    let Some(cutoff) = cutoff else {
+        return Ok(warp::reply::json(&ApiResponse::error("No retention policy configured; pass days=N")));
+    };
+
+    let result = maintenance::prune(&clipboard_data, cutoff);
+    let response = ApiResponse::success(
+        "Clipboard history pruned",
+        Some(serde_json::to_value(&result).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_get_encryption(
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let response = ApiResponse::success(
+        "Encryption status retrieved successfully",
+        Some(serde_json::json!({
+            "encrypted": clipboard_data.is_encrypted(),
+            "locked": clipboard_data.is_locked(),
+        })),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+// 키 유도(Argon2)는 느리므로 DB 잠금을 잡지 않고 blocking 스레드에서 함
+// 다음 시도까지 기다려야 하면 Ok(Err(남은 시간))
+async fn derive_key(
+    key: EncryptionKey,
+    key_attempts: &Mutex<KeyAttempts>,
+    clipboard_data: &Mutex<ClipboardData>,
+) -> Result<Result<DerivedKey, Duration>, EncryptionError> {
+    if let Err(wait) = key_attempts.lock().unwrap().begin(Instant::now()) {
+        return Ok(Err(wait));
+    }
+    let key_check = clipboard_data.lock().unwrap().key_check()?;
+    let derived = tokio::task::spawn_blocking(move || key_check.derive(&key))
+        .await
+        .expect("Key derivation task panicked")?;
+    key_attempts.lock().unwrap().succeed();
+    Ok(Ok(derived))
+}
+
+fn retry_later(wait: Duration) -> ApiResponse {
+    ApiResponse::error(&format!("Too many failed attempts; retry in {} seconds", wait.as_secs() + 1))
+}
+
+async fn handle_unlock(
+    remote: Option<std::net::SocketAddr>,
+    request: KeyRequest,
+    key_file: Option<String>,
+    key_attempts: Arc<Mutex<KeyAttempts>>,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    // 설정의 키 파일은 요청한 쪽이 키를 몰라도 쓸 수 있으므로 같은 컴퓨터에서 온 요청만
+    if request.key_file && !is_local(remote) {
+        return Ok(warp::reply::json(&ApiResponse::error("key_file is only allowed from localhost")));
+    }
+    let Some(key) = request.key(key_file.as_deref()) else {
+        return Ok(warp::reply::json(&ApiResponse::error("passphrase or key_file is required")));
+    };
+    let result = match derive_key(key, &key_attempts, &clipboard_data).await {
+        Ok(Ok(derived)) => clipboard_data.lock().unwrap().unlock_with(derived),
+        Ok(Err(wait)) => return Ok(warp::reply::json(&retry_later(wait))),
+        Err(e) => Err(e),
+    };
+    let response = match result {
+        Ok(()) => ApiResponse::success("Database unlocked", None),
+        Err(e) => {
+            error!("Failed to unlock database: {}", e);
+            ApiResponse::error(&e.to_string())
+        }
+    };
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_rekey(
+    request: RekeyRequest,
+    key_file: Option<String>,
+    key_attempts: Arc<Mutex<KeyAttempts>>,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let current = match request.current.key() {
+        Ok(current) => current,
+        Err(message) => return Ok(warp::reply::json(&ApiResponse::error(&message))),
+    };
+    let key = request.new.key(key_file.as_deref());
+    let encrypted = clipboard_data.lock().unwrap().is_encrypted();
+    let current = match current {
+        Some(current) if encrypted => match derive_key(current, &key_attempts, &clipboard_data).await {
+            Ok(Ok(derived)) => Some(derived),
+            Ok(Err(wait)) => return Ok(warp::reply::json(&retry_later(wait))),
+            Err(e) => {
+                error!("Failed to re-encrypt database: {}", e);
+                return Ok(warp::reply::json(&ApiResponse::error(&e.to_string())));
+            }
+        },
+        _ => None,
+    };
+    let mut clipboard_data = clipboard_data.lock().unwrap();
+    // 암호화된 DB를 바꾸거나 평문으로 되돌리려면 지금 키가 맞아야 함
+    if clipboard_data.is_encrypted() {
+        let Some(current) = current else {
+            return Ok(warp::reply::json(&ApiResponse::error("current passphrase or key_file_contents is required")));
+        };
+        if let Err(e) = clipboard_data.verify_derived_key(&current) {
+            error!("Failed to re-encrypt database: {}", e);
+            return Ok(warp::reply::json(&ApiResponse::error(&e.to_string())));
+        }
+    }
+    let response = match clipboard_data.rekey(key.as_ref()) {
+        Ok(count) => ApiResponse::success(
+            if key.is_some() { "Database re-encrypted" } else { "Database decrypted" },
+            Some(serde_json::json!({ "count": count, "encrypted": clipboard_data.is_encrypted() })),
+        ),
+        Err(e) => {
+            error!("Failed to re-encrypt database: {}", e);
+            ApiResponse::error(&e.to_string())
+        }
+    };
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_get_tags(
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let tags = clipboard_data.list_tags();
+
+    let response = ApiResponse::success(
+        "Tags retrieved successfully",
+        Some(serde_json::to_value(&tags).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_rename_tag(
+    name: String,
+    request: NameRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let renamed = clipboard_data.rename_tag(&decode_name(&name), &request.name);
+
+    let response = ApiResponse::success("Tag renamed successfully", Some(serde_json::json!({ "count": renamed })));
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_delete_tag(
+    name: String,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let deleted = clipboard_data.delete_tag(&decode_name(&name));
+
+    let response = ApiResponse::success("Tag deleted successfully", Some(serde_json::json!({ "count": deleted })));
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_set_item_tags(
+    id: u64,
+    replace: bool,
+    request: TagsRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let updated = if replace {
+        clipboard_data.set_tags(id, &request.tags)
+    } else {
+        clipboard_data.add_tags(id, &request.tags)
+    };
+    if !updated {
+        return Ok(warp::reply::json(&ApiResponse::error("Item not found")));
+    }
+
+    let response = ApiResponse::success("Tags updated successfully", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_remove_item_tag(
+    id: u64,
+    name: String,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.remove_tag(id, &decode_name(&name)) {
+        return Ok(warp::reply::json(&ApiResponse::error("Tag not found on item")));
+    }
+
+    let response = ApiResponse::success("Tag removed successfully", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_get_collections(
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    let collections = clipboard_data.list_collections();
+
+    let response = ApiResponse::success(
+        "Collections retrieved successfully",
+        Some(serde_json::to_value(&collections).unwrap()),
+    );
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_create_collection(
+    request: NameRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.create_collection(&request.name) {
+        return Ok(warp::reply::json(&ApiResponse::error("Collection name is empty or already exists")));
+    }
+
+    let response = ApiResponse::success("Collection created successfully", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_rename_collection(
+    name: String,
+    request: NameRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.rename_collection(&decode_name(&name), &request.name) {
+        return Ok(warp::reply::json(&ApiResponse::error("Collection not found or new name already exists")));
+    }
+
+    let response = ApiResponse::success("Collection renamed successfully", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_delete_collection(
+    name: String,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.delete_collection(&decode_name(&name)) {
+        return Ok(warp::reply::json(&ApiResponse::error("Collection not found")));
+    }
+
+    let response = ApiResponse::success("Collection deleted successfully", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_add_collection_item(
+    name: String,
+    request: CollectionItemRequest,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.add_to_collection(&decode_name(&name), request.id) {
+        return Ok(warp::reply::json(&ApiResponse::error("Collection or item not found")));
+    }
+
+    let response = ApiResponse::success("Item added to collection", None);
+    Ok(warp::reply::json(&response))
+}
+
+async fn handle_remove_collection_item(
+    name: String,
+    id: u64,
+    clipboard_data: Arc<Mutex<ClipboardData>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let clipboard_data = clipboard_data.lock().unwrap();
+    if !clipboard_data.remove_from_collection(&decode_name(&name), id) {
+        return Ok(warp::reply::json(&ApiResponse::error("Item is not in collection")));
+    }
+
+    let response = ApiResponse::success("Item removed from collection", None);
+    Ok(warp::reply::json(&response))
+}
+
 async fn handle_rejection(err: warp::Rejection) -> Result<impl warp::Reply, std::convert::Infallible> {
+    if err.find::<Locked>().is_some() {
+        return Ok(warp::reply::json(&ApiResponse::error("Database is locked")));
+    }
+    if err.find::<NotLocal>().is_some() {
+        return Ok(warp::reply::json(&ApiResponse::error("Only allowed from localhost")));
+    }
+    if err.find::<FromBrowser>().is_some() {
+        return Ok(warp::reply::json(&ApiResponse::error("Not allowed from browsers")));
+    }
     let message = format!("{:?}", err);
     let response = ApiResponse::error(&message);
     Ok(warp::reply::json(&response))
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use rdev::Key;
 use log::{warn, error};
+use crate::database::{DedupMode, EncryptionKey, OversizePolicy};
+use crate::secrets::{SecretAction, SecretPattern};
 
 const SETTINGS_FILE: &str = "pastery.json";
 
@@ -20,6 +22,65 @@ pub struct Settings {
     pub server_port: u16,
     pub max_clipboard_items: usize,
     pub db_path: String,
+    #[serde(default)]
+    pub dedup_mode: DedupMode, // off, consecutive, global
+    #[serde(default = "default_max_item_bytes")]
+    pub max_item_bytes: u64, // 항목 하나의 최대 크기, 0이면 제한 없음
+    #[serde(default)]
+    pub oversize_policy: OversizePolicy, // max_item_bytes를 넘을 때: skip, truncate, external
+    #[serde(default)]
+    pub max_history_bytes: u64, // 기록 전체의 최대 크기, 0이면 제한 없음
+    #[serde(default = "default_compression_threshold")]
+    pub compression_threshold: u64, // 이 크기(바이트)보다 큰 항목은 zstd로 압축해 저장, 0이면 압축하지 않음
+    #[serde(default)]
+    pub retention_days: u64, // 이 일수보다 오래된 항목은 자동 정리, 0이면 기간 제한 없음
+    #[serde(default)]
+    pub midnight_clear_days: Option<u64>, // 매일 자정에 이 일수 이전 날짜의 항목 정리 (0이면 오늘 이전 전부), null이면 사용 안 함
+    #[serde(default = "default_trash_retention_days")]
+    pub trash_retention_days: u64, // 휴지통에서 이 일수가 지난 항목은 완전히 삭제, 0이면 자동으로 비우지 않음
+    #[serde(default = "default_max_memo_revisions")]
+    pub max_memo_revisions: usize, // 메모마다 보관할 이전 버전 수, 0이면 보관하지 않음
+    #[serde(default)]
+    pub encryption: bool, // 클립보드 내용과 메모를 암호화해 저장
+    #[serde(default)]
+    pub encryption_key_file: Option<String>, // 키 파일 경로 (passphrase보다 우선)
+    #[serde(default)]
+    pub encryption_passphrase: Option<String>, // 키 파일과 passphrase가 모두 없으면 잠긴 채로 시작해 POST /unlock을 기다림
+    #[serde(default = "default_secret_detection")]
+    pub secret_detection: bool, // 저장하기 전에 비밀 정보(키, 토큰, 카드 번호 등) 검사
+    #[serde(default)]
+    pub secret_action: SecretAction, // 감지되었을 때: skip, mask, expire
+    #[serde(default = "default_secret_expiry_minutes")]
+    pub secret_expiry_minutes: u64, // expire로 저장한 항목의 보관 시간(분)
+    #[serde(default)]
+    pub secret_patterns: Vec<SecretPattern>, // 사용자 정의 detector: {"name", "pattern"(정규식), "action"(생략 가능)}
+    #[serde(default)]
+    pub disabled_secret_detectors: Vec<String>, // 끌 기본 detector 이름 (e.g., "password")
+}
+
+// 설정 파일에 키가 없을 때 쓰는 값 (Default와 같아야 함)
+fn default_max_item_bytes() -> u64 {
+    10 * 1024 * 1024 // 기본값: 10MB
+}
+
+fn default_compression_threshold() -> u64 {
+    4096 // 기본값: 4KB
+}
+
+fn default_trash_retention_days() -> u64 {
+    30 // 기본값: 30일
+}
+
+fn default_max_memo_revisions() -> usize {
+    20 // 기본값: 20개
+}
+
+fn default_secret_detection() -> bool {
+    true
+}
+
+fn default_secret_expiry_minutes() -> u64 {
+    5 // 기본값: 5분
 }
 
 impl Default for Settings {
@@ -48,6 +109,23 @@ impl Default for Settings {
             server_port: 3030,
             max_clipboard_items: 1000, // 기본값: 1000개
             db_path: db_path,
+            dedup_mode: DedupMode::default(),
+            max_item_bytes: default_max_item_bytes(),
+            oversize_policy: OversizePolicy::default(),
+            max_history_bytes: 0,
+            compression_threshold: default_compression_threshold(),
+            retention_days: 0,
+            midnight_clear_days: None,
+            trash_retention_days: default_trash_retention_days(),
+            max_memo_revisions: default_max_memo_revisions(),
+            encryption: false,
+            encryption_key_file: None,
+            encryption_passphrase: None,
+            secret_detection: default_secret_detection(),
+            secret_action: SecretAction::default(),
+            secret_expiry_minutes: default_secret_expiry_minutes(),
+            secret_patterns: Vec::new(),
+            disabled_secret_detectors: Vec::new(),
         }
     }
 }
@@ -75,6 +153,12 @@ impl Settings {
         }
     }
 
+    // 설정된 암호화 키 (키 파일 우선)
+    pub fn encryption_key(&self) -> Option<EncryptionKey> {
+        self.encryption_key_file.clone().map(EncryptionKey::KeyFile)
+            .or_else(|| self.encryption_passphrase.clone().map(EncryptionKey::Passphrase))
+    }
+
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
         let content = serde_json::to_string_pretty(self)?;
         fs::write(SETTINGS_FILE, content)?;
@@ -129,3 +213,40 @@ pub fn key_binding_to_keys(binding: &KeyBinding) -> Vec<Key> {
     keys.push(main_key);
     keys
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 새 설정 키가 없는 예전 pastery.json은 Default와 같은 값으로 읽혀야 함
+    #[test]
+    fn test_missing_keys_use_defaults() {
+        let content = r#"{
+            "copy_key": { "ctrl": true, "alt": false, "shift": false, "key": "c" },
+            "paste_key": { "ctrl": true, "alt": false, "shift": false, "key": "v" },
+            "server_port": 3030,
+            "max_clipboard_items": 1000,
+            "db_path": "/tmp/Pastery/clip.db"
+        }"#;
+        let settings: Settings = serde_json::from_str(content).unwrap();
+        let defaults = Settings::default();
+
+        assert_eq!(settings.dedup_mode, defaults.dedup_mode);
+        assert_eq!(settings.max_item_bytes, defaults.max_item_bytes);
+        assert_eq!(settings.oversize_policy, defaults.oversize_policy);
+        assert_eq!(settings.max_history_bytes, defaults.max_history_bytes);
+        assert_eq!(settings.compression_threshold, defaults.compression_threshold);
+        assert_eq!(settings.retention_days, defaults.retention_days);
+        assert_eq!(settings.midnight_clear_days, defaults.midnight_clear_days);
+        assert_eq!(settings.trash_retention_days, defaults.trash_retention_days);
+        assert_eq!(settings.max_memo_revisions, defaults.max_memo_revisions);
+        assert_eq!(settings.encryption, defaults.encryption);
+        assert_eq!(settings.encryption_key_file, defaults.encryption_key_file);
+        assert_eq!(settings.encryption_passphrase, defaults.encryption_passphrase);
+        assert_eq!(settings.secret_detection, defaults.secret_detection);
+        assert_eq!(settings.secret_action, defaults.secret_action);
+        assert_eq!(settings.secret_expiry_minutes, defaults.secret_expiry_minutes);
+        assert!(settings.secret_patterns.is_empty() && defaults.secret_patterns.is_empty());
+        assert_eq!(settings.disabled_secret_detectors, defaults.disabled_secret_detectors);
+    }
+}
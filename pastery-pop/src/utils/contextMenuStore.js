@@ -10,7 +10,9 @@ export const contextMenuStore = writable({
 // 편집 다이얼로그 상태 관리
 export const editDialogStore = writable({
   visible: false,
-  content: ''
+  content: '',
+  revisions: [], // 되돌릴 수 있는 이전 버전 (사용자 메모만)
+  details: null // { title, description, folder } (사용자 메모만)
 });
 
 // 컨텍스트 메뉴 표시
@@ -35,10 +37,12 @@ export function hideContextMenu() {
 }
 
 // 편집 다이얼로그 표시
-export function showEditDialog(content) {
+export function showEditDialog(content, revisions = [], details = null) {
   editDialogStore.set({
     visible: true,
-    content: content || ''
+    content: content || '',
+    revisions,
+    details
   });
 }
 
@@ -46,7 +50,9 @@ export function showEditDialog(content) {
 export function hideEditDialog() {
   editDialogStore.set({
     visible: false,
-    content: ''
+    content: '',
+    revisions: [],
+    details: null
   });
 }
 